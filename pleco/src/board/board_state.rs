@@ -239,6 +239,32 @@ impl BoardState {
         (&self).prev.as_ref().cloned()
     }
 
+    /// Returns the previous `BoardState` if the move leading to this state can be followed by a
+    /// repetition of an earlier position. Returns `None` if there is no previous state, or the
+    /// last move was a capture, pawn move, or null move.
+    pub fn reversible_prev(&self) -> Option<&BoardState> {
+        if self.rule_50 == 0 || self.prev_move.is_null() {
+            None
+        } else {
+            self.prev.as_ref().map(|prev| &**prev)
+        }
+    }
+
+    /// Returns if the position of this state has occurred before, since the last capture, pawn
+    /// move, or null move.
+    pub fn is_repeated(&self) -> bool {
+        let mut state: &BoardState = self;
+        let mut plies: u16 = 0;
+        while let Some(prev) = state.reversible_prev() {
+            state = prev;
+            plies += 1;
+            if plies % 2 == 0 && state.zobrist == self.zobrist {
+                return true;
+            }
+        }
+        false
+    }
+
     /// Iterates through all previous `BoardStates` and prints debug information for each.
     ///
     /// Used primarily for debugging.
@@ -84,16 +84,7 @@ impl Castling {
     /// Returns if a player can castle for a given side
     #[inline]
     pub fn castle_rights(&self, player: Player, side: CastleType) -> bool {
-        match player {
-            Player::White => match side {
-                CastleType::KingSide => self.contains(Self::WHITE_K),
-                CastleType::QueenSide => self.contains(Self::WHITE_Q),
-            },
-            Player::Black => match side {
-                CastleType::KingSide => self.contains(Self::BLACK_K),
-                CastleType::QueenSide => self.contains(Self::BLACK_Q),
-            },
-        }
+        self.contains(Castling::castle_bit(player, side))
     }
 
     #[inline]
@@ -112,9 +103,37 @@ impl Castling {
             && !self.contains(Castling::BLACK_Q)
     }
 
+    /// Returns the castling bit for a player and side.
+    #[inline]
+    pub fn castle_bit(player: Player, side: CastleType) -> Castling {
+        match player {
+            Player::White => match side {
+                CastleType::KingSide => Self::WHITE_K,
+                CastleType::QueenSide => Self::WHITE_Q,
+            },
+            Player::Black => match side {
+                CastleType::KingSide => Self::BLACK_K,
+                CastleType::QueenSide => Self::BLACK_Q,
+            },
+        }
+    }
+
+    /// Adds the right to castle for a player and side.
+    #[inline]
+    pub fn add_castling_right(&mut self, player: Player, side: CastleType) {
+        self.insert(Castling::castle_bit(player, side));
+    }
+
+    /// Removes the castling rights of a standard chess game that are lost by moving a piece
+    /// from or to the given squares. Returns the bits that were removed.
     #[inline]
     pub fn update_castling(&mut self, to: SQ, from: SQ) -> u8 {
-        let mask_change: u8 = to.castle_rights_mask() | from.castle_rights_mask();
+        self.update_castling_mask(to.castle_rights_mask() | from.castle_rights_mask())
+    }
+
+    /// Removes the castling rights contained in `mask_change`. Returns the bits that were removed.
+    #[inline]
+    pub fn update_castling_mask(&mut self, mask_change: u8) -> u8 {
         let to_return: u8 = self.bits & mask_change;
         self.bits &= !mask_change;
         to_return
@@ -0,0 +1,468 @@
+//! Module for reading and writing EPD (Extended Position Description) records.
+//!
+//! An EPD record is a single line, containing the first four fields of a FEN string (piece
+//! placement, side to move, castling rights and en-passant square), followed by a list of
+//! operations. Each operation is an opcode followed by zero or more operands, and is ended by
+//! a semicolon. Test suites such as WAC, STS and the Bratko-Kopec set are distributed as EPD.
+//!
+//! ```md,ignore
+//! 2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";
+//! ```
+//!
+//! The following opcodes are understood, and their moves are resolved to `BitMove`s:
+//!
+//! ```md,ignore
+//! bm       best moves, in SAN
+//! am       moves to avoid, in SAN
+//! id       identifier of the record
+//! c0 - c9  comments
+//! ce       centipawn evaluation, from the side to move's point of view
+//! dm       direct mate, in full moves
+//! pv       predicted variation, in SAN
+//! hmvc     half-move clock
+//! fmvn     full-move number
+//! ```
+//!
+//! Any other opcode is kept as is.
+//!
+//! # Examples
+//!
+//! ```
+//! use pleco::board::epd::EPD;
+//!
+//! let line = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+//! let epd = EPD::parse(line).unwrap();
+//! assert_eq!(epd.id.as_ref().unwrap(), "WAC.001");
+//! assert_eq!(epd.best_moves[0].stringify(), "g3g6");
+//! assert_eq!(epd.to_string(), line);
+//! ```
+//!
+//! See the [PGN Specification](https://www.chessclub.com/user/help/PGN-spec), section 16.2, for
+//! more information.
+
+use super::{Board, FenBuildError, SanError};
+use core::piece_move::BitMove;
+use std::fmt;
+use std::num;
+
+/// The number of comment opcodes, `c0` through `c9`.
+pub const EPD_COMMENT_CNT: usize = 10;
+
+/// Represents possible Errors encountered while parsing an EPD record.
+#[derive(Debug)]
+pub enum EPDError {
+    /// The record has less than the four position fields.
+    Length,
+    /// The position fields do not describe a valid position.
+    FenBuild(FenBuildError),
+    /// An operation is not ended by a semicolon, or contains an unterminated string.
+    UnterminatedOperation { opcode: String },
+    /// An operand is missing, or cannot be parsed for its opcode.
+    Operand { opcode: String, operand: String },
+    /// A move of a `bm`, `am` or `pv` operation is not a legal move.
+    Move { opcode: String, err: SanError },
+}
+
+impl From<FenBuildError> for EPDError {
+    fn from(err: FenBuildError) -> EPDError {
+        EPDError::FenBuild(err)
+    }
+}
+
+/// A single parsed EPD record.
+#[derive(Clone, Debug)]
+pub struct EPD {
+    /// The position of the record. The half-move clock and full-move number of the board are
+    /// taken from the `hmvc` and `fmvn` operations, if present.
+    pub board: Board,
+    /// The best moves of the position, from the `bm` opcode.
+    pub best_moves: Vec<BitMove>,
+    /// The moves to avoid in the position, from the `am` opcode.
+    pub avoid_moves: Vec<BitMove>,
+    /// The identifier of the record, from the `id` opcode.
+    pub id: Option<String>,
+    /// The comments of the record, from the `c0` through `c9` opcodes.
+    pub comments: [Option<String>; EPD_COMMENT_CNT],
+    /// The centipawn evaluation of the position, from the `ce` opcode.
+    pub centipawn_eval: Option<i32>,
+    /// The number of moves to a forced mate, from the `dm` opcode.
+    pub direct_mate: Option<u16>,
+    /// The predicted variation of the position, from the `pv` opcode.
+    pub pv: Vec<BitMove>,
+    /// The half-move clock, from the `hmvc` opcode.
+    pub half_move_clock: Option<u16>,
+    /// The full-move number, from the `fmvn` opcode.
+    pub full_move_number: Option<u16>,
+    /// Any operations with other opcodes, with their operands as written in the record.
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl EPD {
+    /// Creates an EPD record of a position, with no operations.
+    pub fn new(board: Board) -> EPD {
+        EPD {
+            board,
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+            id: None,
+            comments: Default::default(),
+            centipawn_eval: None,
+            direct_mate: None,
+            pv: Vec::new(),
+            half_move_clock: None,
+            full_move_number: None,
+            operations: Vec::new(),
+        }
+    }
+
+    /// Parses a single EPD record.
+    pub fn parse(line: &str) -> Result<EPD, EPDError> {
+        let line = line.trim();
+        let mut fields: Vec<&str> = Vec::with_capacity(4);
+        let mut rest: &str = line;
+        for _ in 0..4 {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or_else(|| rest.len());
+            if end == 0 {
+                return Err(EPDError::Length);
+            }
+            fields.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+        let operations = parse_operations(rest)?;
+
+        let mut half_move_clock = None;
+        let mut full_move_number = None;
+        for &(ref opcode, ref operands) in operations.iter() {
+            match opcode.as_str() {
+                "hmvc" => half_move_clock = Some(parse_operand(opcode, operands)?),
+                "fmvn" => full_move_number = Some(parse_operand(opcode, operands)?),
+                _ => {}
+            }
+        }
+
+        let fen = format!(
+            "{} {} {}",
+            fields.join(" "),
+            half_move_clock.unwrap_or(0),
+            full_move_number.unwrap_or(1)
+        );
+        let mut epd = EPD::new(Board::from_fen(&fen)?);
+        epd.half_move_clock = half_move_clock;
+        epd.full_move_number = full_move_number;
+
+        for (opcode, operands) in operations {
+            match opcode.as_str() {
+                "bm" => epd.best_moves = epd.parse_moves(&opcode, &operands, false)?,
+                "am" => epd.avoid_moves = epd.parse_moves(&opcode, &operands, false)?,
+                "pv" => epd.pv = epd.parse_moves(&opcode, &operands, true)?,
+                "id" => epd.id = Some(string_operand(&opcode, &operands)?),
+                "ce" => epd.centipawn_eval = Some(parse_operand(&opcode, &operands)?),
+                "dm" => epd.direct_mate = Some(parse_operand(&opcode, &operands)?),
+                "hmvc" | "fmvn" => {}
+                _ => match comment_index(&opcode) {
+                    Some(i) => epd.comments[i] = Some(string_operand(&opcode, &operands)?),
+                    None => epd.operations.push((opcode, operands)),
+                },
+            }
+        }
+        Ok(epd)
+    }
+
+    /// Parses every record of a file, one record per line. Empty lines are skipped.
+    pub fn parse_all(input: &str) -> Result<Vec<EPD>, EPDError> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(EPD::parse)
+            .collect()
+    }
+
+    // Resolves SAN operands to moves. If `sequence` is true, each move is played before
+    // resolving the next one, as in a `pv` operation.
+    fn parse_moves(
+        &self,
+        opcode: &str,
+        operands: &[String],
+        sequence: bool,
+    ) -> Result<Vec<BitMove>, EPDError> {
+        if operands.is_empty() {
+            return Err(EPDError::Operand {
+                opcode: opcode.to_string(),
+                operand: String::new(),
+            });
+        }
+        let mut board = self.board.shallow_clone();
+        let mut moves = Vec::with_capacity(operands.len());
+        for san in operands.iter() {
+            let mov = board.parse_san(san).map_err(|err| EPDError::Move {
+                opcode: opcode.to_string(),
+                err,
+            })?;
+            if sequence {
+                board.apply_move(mov);
+            }
+            moves.push(mov);
+        }
+        Ok(moves)
+    }
+
+    // Writes moves as SAN, playing each move before the next if `sequence` is true.
+    fn sans(&self, moves: &[BitMove], sequence: bool) -> Vec<String> {
+        let mut board = self.board.shallow_clone();
+        let mut sans = Vec::with_capacity(moves.len());
+        for mov in moves.iter() {
+            sans.push(board.san(*mov));
+            if sequence {
+                board.apply_move(*mov);
+            }
+        }
+        sans
+    }
+}
+
+/// Displays the record as a single line of EPD.
+impl fmt::Display for EPD {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fen = self.board.fen();
+        let mut s: String = fen
+            .split_whitespace()
+            .take(4)
+            .collect::<Vec<&str>>()
+            .join(" ");
+
+        let mut push_op = |opcode: &str, operands: &[String]| {
+            s.push(' ');
+            s.push_str(opcode);
+            for operand in operands.iter() {
+                s.push(' ');
+                s.push_str(operand);
+            }
+            s.push(';');
+        };
+
+        if !self.best_moves.is_empty() {
+            push_op("bm", &self.sans(&self.best_moves, false));
+        }
+        if !self.avoid_moves.is_empty() {
+            push_op("am", &self.sans(&self.avoid_moves, false));
+        }
+        if let Some(ref id) = self.id {
+            push_op("id", &[quote(id)]);
+        }
+        for (i, comment) in self.comments.iter().enumerate() {
+            if let Some(ref comment) = *comment {
+                push_op(&format!("c{}", i), &[quote(comment)]);
+            }
+        }
+        if let Some(ce) = self.centipawn_eval {
+            push_op("ce", &[ce.to_string()]);
+        }
+        if let Some(dm) = self.direct_mate {
+            push_op("dm", &[dm.to_string()]);
+        }
+        if !self.pv.is_empty() {
+            push_op("pv", &self.sans(&self.pv, true));
+        }
+        if let Some(hmvc) = self.half_move_clock {
+            push_op("hmvc", &[hmvc.to_string()]);
+        }
+        if let Some(fmvn) = self.full_move_number {
+            push_op("fmvn", &[fmvn.to_string()]);
+        }
+        for &(ref opcode, ref operands) in self.operations.iter() {
+            push_op(opcode, operands);
+        }
+        write!(f, "{}", s)
+    }
+}
+
+// Returns the index of a comment opcode, `c0` through `c9`.
+fn comment_index(opcode: &str) -> Option<usize> {
+    let mut chars = opcode.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some('c'), Some(digit), None) => digit.to_digit(10).map(|d| d as usize),
+        _ => None,
+    }
+}
+
+// Parses the single numeric operand of an operation.
+fn parse_operand<T>(opcode: &str, operands: &[String]) -> Result<T, EPDError>
+where
+    T: ::std::str::FromStr<Err = num::ParseIntError>,
+{
+    match operands {
+        [ref operand] => operand.parse::<T>().map_err(|_| EPDError::Operand {
+            opcode: opcode.to_string(),
+            operand: operand.clone(),
+        }),
+        _ => Err(EPDError::Operand {
+            opcode: opcode.to_string(),
+            operand: operands.join(" "),
+        }),
+    }
+}
+
+// Returns the single string operand of an operation, without its quotes.
+fn string_operand(opcode: &str, operands: &[String]) -> Result<String, EPDError> {
+    match operands {
+        [ref operand] => Ok(unquote(operand).to_string()),
+        _ => Err(EPDError::Operand {
+            opcode: opcode.to_string(),
+            operand: operands.join(" "),
+        }),
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s)
+}
+
+fn unquote(s: &str) -> &str {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        &s[1..s.len() - 1]
+    } else {
+        s
+    }
+}
+
+// Splits the operations of a record into opcodes and operands. String operands keep their
+// quotes, and may contain whitespace and semicolons.
+fn parse_operations(input: &str) -> Result<Vec<(String, Vec<String>)>, EPDError> {
+    let mut operations = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    loop {
+        while chars.peek().map_or(false, |&(_, c)| c.is_whitespace()) {
+            chars.next();
+        }
+        let start = match chars.peek() {
+            Some(&(i, _)) => i,
+            None => return Ok(operations),
+        };
+
+        // Read the opcode, followed by each operand up to the semicolon
+        let mut tokens: Vec<String> = Vec::new();
+        let mut token_start = Some(start);
+        let mut in_string = false;
+        let mut terminated = false;
+        while let Some((i, c)) = chars.next() {
+            if in_string {
+                if c == '"' {
+                    in_string = false;
+                }
+                continue;
+            }
+            match c {
+                '"' if token_start.is_none() => {
+                    token_start = Some(i);
+                    in_string = true;
+                }
+                ';' | ' ' | '\t' => {
+                    if let Some(begin) = token_start.take() {
+                        tokens.push(input[begin..i].to_string());
+                    }
+                    if c == ';' {
+                        terminated = true;
+                        break;
+                    }
+                }
+                _ => {
+                    if token_start.is_none() {
+                        token_start = Some(i);
+                    }
+                }
+            }
+        }
+
+        let opcode = tokens.first().cloned().unwrap_or_default();
+        if !terminated {
+            return Err(EPDError::UnterminatedOperation { opcode });
+        }
+        if !opcode.is_empty() {
+            let operands = tokens.split_off(1);
+            operations.push((opcode, operands));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRATKO_KOPEC: &str = "\
+1k1r4/pp1b1R2/3q2pp/4p3/2B5/4Q3/PPP2B2/2K5 b - - bm Qd1+; id \"BK.01\";
+3r1k2/4npp1/1ppr3p/p6P/P2PPPP1/1NR5/5K2/2R5 w - - bm d5; id \"BK.02\";
+
+2q1rr1k/3bbnnp/p2p1pp1/2pPp3/PpP1P1P1/1P2BNNP/2BQ1PRK/7R b - - bm f5; id \"BK.03\";
+rnbqkb1r/p3pppp/1p6/2ppP3/3N4/2P5/PPP1QPPP/R1B1KB1R w KQkq - bm e6; id \"BK.04\";
+";
+
+    #[test]
+    fn parse_suite() {
+        let records = EPD::parse_all(BRATKO_KOPEC).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].id.as_ref().unwrap(), "BK.01");
+        assert_eq!(records[0].best_moves[0].stringify(), "d6d1");
+        assert_eq!(records[1].best_moves[0].stringify(), "d4d5");
+        assert_eq!(
+            records[3].board.fen(),
+            "rnbqkb1r/p3pppp/1p6/2ppP3/3N4/2P5/PPP1QPPP/R1B1KB1R w KQkq - 0 1"
+        );
+
+        for (record, line) in records
+            .iter()
+            .zip(BRATKO_KOPEC.lines().filter(|l| !l.is_empty()))
+        {
+            assert_eq!(record.to_string(), line);
+        }
+    }
+
+    #[test]
+    fn opcodes() {
+        let line = "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - \
+                    am Ng5 d3; bm O-O; ce 35; dm 12; pv O-O Nxe4 Re1; hmvc 4; fmvn 5; \
+                    c0 \"Italian; Two Knights\"; c9 \"ok\"; id \"italian\"; acd 20; sv Ng5 Nxe4;";
+        let epd = EPD::parse(line).unwrap();
+        assert_eq!(epd.avoid_moves.len(), 2);
+        assert_eq!(epd.best_moves[0], epd.pv[0]);
+        assert!(epd.best_moves[0].is_castle());
+        assert_eq!(epd.centipawn_eval, Some(35));
+        assert_eq!(epd.direct_mate, Some(12));
+        assert_eq!(epd.pv.len(), 3);
+        assert_eq!(epd.pv[2].stringify(), "f1e1");
+        assert_eq!(epd.board.rule_50(), 4);
+        assert_eq!(epd.board.moves_played(), 8);
+        assert_eq!(epd.comments[0].as_ref().unwrap(), "Italian; Two Knights");
+        assert_eq!(epd.comments[9].as_ref().unwrap(), "ok");
+        assert_eq!(epd.id.as_ref().unwrap(), "italian");
+        assert_eq!(
+            epd.operations[0],
+            ("acd".to_string(), vec!["20".to_string()])
+        );
+        assert_eq!(epd.operations[1].1.len(), 2);
+
+        let written = epd.to_string();
+        assert_eq!(
+            written,
+            "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - \
+             bm O-O; am Ng5 d3; id \"italian\"; c0 \"Italian; Two Knights\"; c9 \"ok\"; \
+             ce 35; dm 12; pv O-O Nxe4 Re1; hmvc 4; fmvn 5; acd 20; sv Ng5 Nxe4;"
+        );
+        assert_eq!(EPD::parse(&written).unwrap().to_string(), written);
+    }
+
+    #[test]
+    fn errors() {
+        let pos = "4k3/8/8/8/8/8/8/R3K3 w Q -";
+        assert!(EPD::parse(pos).unwrap().best_moves.is_empty());
+        assert!(EPD::parse("4k3/8/8/8/8/8/8/R3K3 w").is_err());
+        assert!(EPD::parse(&format!("{} bm Rh1;", pos)).is_err());
+        assert!(EPD::parse(&format!("{} c0 \"unterminated;", pos)).is_err());
+        assert!(EPD::parse(&format!("{} ce high;", pos)).is_err());
+        assert!(EPD::parse(&format!("{} bm;", pos)).is_err());
+        match EPD::parse(&format!("{} bm Rb1; am Kd3;", pos)) {
+            Err(EPDError::Move { opcode, .. }) => assert_eq!(opcode, "am"),
+            _ => panic!(),
+        }
+    }
+}
@@ -34,15 +34,20 @@ use tools::{PreFetchable, Searcher};
 use self::board_state::BoardState;
 use self::castle_rights::Castling;
 use self::movegen::{Legal, MoveGen, PseudoLegal};
+use self::outcome::{Outcome, Termination};
+use self::pgn::PGNMove;
 use self::piece_locations::PieceLocations;
 
 pub mod board_state;
 pub mod castle_rights;
+pub mod epd;
 pub mod fen;
 pub mod movegen;
+pub mod outcome;
 pub mod perft;
-mod pgn;
+pub mod pgn;
 pub mod piece_locations;
+pub mod polyglot;
 
 /// Represents possible Errors encountered while building a `Board` from a fen string.
 pub enum FenBuildError {
@@ -72,6 +77,9 @@ pub enum FenBuildError {
     UnrecognizedPiece {
         piece: char,
     },
+    UnrecognizedCastling {
+        castling: String,
+    },
     UnreadableMoves(num::ParseIntError),
     IllegalNumCheckingPieces {
         num: u8,
@@ -85,6 +93,10 @@ pub enum FenBuildError {
         num: u8,
     },
     PawnOnLastRow,
+    IllegalNumKings {
+        player: Player,
+        num: u8,
+    },
 }
 
 impl From<num::ParseIntError> for FenBuildError {
@@ -126,6 +138,9 @@ impl fmt::Debug for FenBuildError {
             FenBuildError::UnrecognizedPiece { piece } => {
                 writeln!(f, "unrecognized piece: {}", piece)
             }
+            FenBuildError::UnrecognizedCastling { ref castling } => {
+                writeln!(f, "invalid castling rights: {}", castling)
+            }
             FenBuildError::UnreadableMoves(ref err) => {
                 writeln!(f, "An unknown error has occurred {:?}", err)
             }
@@ -143,6 +158,31 @@ impl fmt::Debug for FenBuildError {
                 player, num
             ),
             FenBuildError::PawnOnLastRow => writeln!(f, "Pawn on first or last row"),
+            FenBuildError::IllegalNumKings { player, num } => writeln!(
+                f,
+                "each player needs exactly one king: player: {}, # kings {}",
+                player, num
+            ),
+        }
+    }
+}
+
+/// Represents possible Errors encountered while reading a move in Standard Algebraic Notation.
+pub enum SanError {
+    /// The move could not be parsed as SAN.
+    Unparseable { san: String },
+    /// No legal move of the position matches the move.
+    IllegalMove { san: String },
+    /// More than one legal move of the position matches the move.
+    AmbiguousMove { san: String },
+}
+
+impl fmt::Debug for SanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SanError::Unparseable { ref san } => writeln!(f, "unparseable SAN move: {}", san),
+            SanError::IllegalMove { ref san } => writeln!(f, "illegal SAN move: {}", san),
+            SanError::AmbiguousMove { ref san } => writeln!(f, "ambiguous SAN move: {}", san),
         }
     }
 }
@@ -207,6 +247,11 @@ pub struct Board {
     piece_counts: [[u8; PIECE_TYPE_CNT]; PLAYER_CNT], // Count of each Piece
     piece_locations: PieceLocations,                  // Mapping Squares to Pieces and Plauers
 
+    chess960: bool,                                          // Chess960 castling rules
+    castling_rights_mask: [u8; SQ_CNT],                      // Castling rights lost per square
+    castling_rook_sq: [[SQ; CASTLING_SIDES]; PLAYER_CNT],    // Starting Rook for each castle
+    castling_path: [[BitBoard; CASTLING_SIDES]; PLAYER_CNT], // Squares to be empty to castle
+
     // State of the Board, Un modifiable.
     // Arc to allow easy and quick copying of boards without copying memory
     // or recomputing BoardStates.
@@ -302,6 +347,10 @@ impl Board {
             depth: 0,
             piece_counts: self.piece_counts,
             piece_locations: self.piece_locations.clone(),
+            chess960: self.chess960,
+            castling_rights_mask: self.castling_rights_mask,
+            castling_rook_sq: self.castling_rook_sq,
+            castling_path: self.castling_path,
             state: Arc::clone(&self.state),
             magic_helper: self.magic_helper,
         }
@@ -343,6 +392,10 @@ impl Board {
             depth: self.depth,
             piece_counts: self.piece_counts,
             piece_locations: self.piece_locations.clone(),
+            chess960: self.chess960,
+            castling_rights_mask: self.castling_rights_mask,
+            castling_rook_sq: self.castling_rook_sq,
+            castling_path: self.castling_path,
             state: Arc::clone(&self.state),
             magic_helper: self.magic_helper,
         }
@@ -404,6 +457,94 @@ impl Board {
     /// The Constructed Board may have some Undefined Behavior as a result. It is up to the user to give a
     /// valid FEN string.
     pub fn from_fen(fen: &str) -> Result<Board, FenBuildError> {
+        Board::from_fen_inner(fen, false)
+    }
+
+    /// Constructs a Chess960 (Fischer Random Chess) board from a FEN String.
+    ///
+    /// Unlike `Board::from_fen`, the board is always in Chess960 mode, even if the kings and rooks
+    /// start from their standard squares. This only changes how castling moves are written in UCI
+    /// notation, where the king captures its own rook (e.g. "e1h1" instead of "e1g1").
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let board = Board::from_chess960_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    /// assert!(board.is_chess960());
+    /// assert_eq!(board.shredder_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1");
+    /// ```
+    pub fn from_chess960_fen(fen: &str) -> Result<Board, FenBuildError> {
+        Board::from_fen_inner(fen, true)
+    }
+
+    /// Constructs the starting position of a Chess960 game from its Scharnagl number, from 0 to 959.
+    /// Position number 518 is the standard starting position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let board = Board::chess960_start_pos(0);
+    /// assert_eq!(board.fen(), "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position number is greater than 959.
+    pub fn chess960_start_pos(number: u16) -> Board {
+        assert!(number < 960);
+        let mut rank: [char; FILE_CNT] = [' '; FILE_CNT];
+        let mut n = number as usize;
+
+        // Bishops on a light and a dark square
+        rank[(n % 4) * 2 + 1] = 'B';
+        n /= 4;
+        rank[(n % 4) * 2] = 'B';
+        n /= 4;
+
+        // Queen, and then the Knights, on the remaining empty squares
+        let place = |rank: &mut [char; FILE_CNT], mut idx: usize, piece: char| {
+            for file in rank.iter_mut().filter(|f| **f == ' ') {
+                if idx == 0 {
+                    *file = piece;
+                    return;
+                }
+                idx -= 1;
+            }
+        };
+        place(&mut rank, n % 6, 'Q');
+        n /= 6;
+        let (n1, n2) = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (2, 2),
+            (2, 3),
+            (3, 3),
+        ][n];
+        place(&mut rank, n1, 'N');
+        place(&mut rank, n2, 'N');
+
+        // Rook, King, Rook on the last three squares
+        place(&mut rank, 0, 'R');
+        place(&mut rank, 0, 'K');
+        place(&mut rank, 0, 'R');
+
+        let white: String = rank.iter().collect();
+        let black: String = white.to_lowercase();
+        let fen = format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w KQkq - 0 1", black, white);
+        Board::from_chess960_fen(&fen).unwrap()
+    }
+
+    // Constructs a board from a FEN String, forcing Chess960 mode if `chess960` is true.
+    fn from_fen_inner(fen: &str, chess960: bool) -> Result<Board, FenBuildError> {
         // split the string by white space
         let det_split: Vec<&str> = fen.split_whitespace().collect();
 
@@ -433,6 +574,16 @@ impl Board {
             depth: 0,
             piece_counts: [[0; PIECE_TYPE_CNT]; PLAYER_CNT],
             piece_locations: PieceLocations::blank(),
+            chess960: false,
+            castling_rights_mask: [0; SQ_CNT],
+            castling_rook_sq: [
+                [SQ(CASTLING_ROOK_START[0][0]), SQ(CASTLING_ROOK_START[0][1])],
+                [SQ(CASTLING_ROOK_START[1][0]), SQ(CASTLING_ROOK_START[1][1])],
+            ],
+            castling_path: [
+                [BitBoard(CASTLING_PATH[0][0]), BitBoard(CASTLING_PATH[0][1])],
+                [BitBoard(CASTLING_PATH[1][0]), BitBoard(CASTLING_PATH[1][1])],
+            ],
             state: Arc::new(BoardState::blank()),
             magic_helper: Helper::new(),
         };
@@ -463,10 +614,7 @@ impl Board {
         b.turn = turn;
 
         // Castle Bytes
-        let mut castle_bytes = Castling::empty();
-        for ch in det_split[2].chars() {
-            castle_bytes.add_castling_char(ch);
-        }
+        let castle_bytes = b.set_castling_rights(det_split[2], chess960)?;
 
         let mut ep_sq: SQ = SQ(0);
         for (i, character) in det_split[3].chars().enumerate() {
@@ -537,6 +685,17 @@ impl Board {
 
         b.half_moves = total_moves;
 
+        // The check information below needs both kings on the board
+        for player in ALL_PLAYERS.iter() {
+            let num = b.count_piece(*player, PieceType::K);
+            if num != 1 {
+                return Err(FenBuildError::IllegalNumKings {
+                    player: *player,
+                    num,
+                });
+            }
+        }
+
         // Set State info
         let b_state = {
             // Set Check info
@@ -567,7 +726,33 @@ impl Board {
     /// let board = Board::start_pos();
     /// assert_eq!(board.fen(),"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
     /// ```
+    ///
+    /// In Chess960 mode, castling rights are written as X-FEN: `K` and `Q` (or `k` and `q`) for
+    /// the outermost rook of each side, and the rook's file otherwise.
     pub fn fen(&self) -> String {
+        self.fen_inner(false)
+    }
+
+    /// Creates a Shredder-FEN String of the Given Board.
+    ///
+    /// Shredder-FEN is the same as FEN, except castling rights are always written as the files of
+    /// the castling rooks, such as `HAha` for the starting position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let board = Board::from_fen("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1").unwrap();
+    /// assert_eq!(board.fen(), "1r2k1r1/8/8/8/8/8/8/1R2K1R1 w KQkq - 0 1");
+    /// assert_eq!(board.shredder_fen(), "1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1");
+    /// ```
+    pub fn shredder_fen(&self) -> String {
+        self.fen_inner(true)
+    }
+
+    // Creates a FEN String, writing the castling rights as Shredder-FEN if `shredder` is true.
+    fn fen_inner(&self, shredder: bool) -> String {
         // TODO: Doesnt display if rank 8 has zero pieces on it
         let mut s = String::default();
 
@@ -606,7 +791,7 @@ impl Board {
         s.push(' ');
 
         // Castling State
-        s.push_str(&(self.state.castling.pretty_string()));
+        s.push_str(&self.castling_string(shredder));
         s.push(' ');
 
         // EP Square
@@ -625,6 +810,47 @@ impl Board {
         s
     }
 
+    // Returns the castling rights field of a FEN string.
+    fn castling_string(&self, shredder: bool) -> String {
+        if !self.chess960 && !shredder {
+            return self.state.castling.pretty_string();
+        }
+        let mut s = String::default();
+        for &player in ALL_PLAYERS.iter() {
+            for &side in [CastleType::KingSide, CastleType::QueenSide].iter() {
+                if !self.can_castle(player, side) {
+                    continue;
+                }
+                let r_sq: SQ = self.castling_rook_sq[player as usize][side as usize];
+                let r_file = r_sq.file_idx_of_sq();
+                let rooks: BitBoard = self.piece_bb(player, PieceType::R) & r_sq.rank_bb();
+                let outer: BitBoard = (0..FILE_CNT as u8)
+                    .filter(|f| match side {
+                        CastleType::KingSide => *f > r_file,
+                        CastleType::QueenSide => *f < r_file,
+                    })
+                    .fold(BitBoard(0), |bb, f| bb | SQ((r_sq.0 & !0b111) | f).to_bb());
+
+                let c: char = if !shredder && (rooks & outer).is_empty() {
+                    match side {
+                        CastleType::KingSide => 'k',
+                        CastleType::QueenSide => 'q',
+                    }
+                } else {
+                    FILE_DISPLAYS[r_file as usize]
+                };
+                s.push(match player {
+                    Player::White => c.to_ascii_uppercase(),
+                    Player::Black => c,
+                });
+            }
+        }
+        if s.is_empty() {
+            s.push('-');
+        }
+        s
+    }
+
     /// Applies a move to the Board.
     ///
     /// # Safety
@@ -783,10 +1009,10 @@ impl Board {
             }
 
             // Update castling rights
-            if !new_state.castling.is_empty()
-                && (to.castle_rights_mask() | from.castle_rights_mask()) != 0
-            {
-                let castle_zob_index = new_state.castling.update_castling(to, from);
+            let castle_mask: u8 = self.castling_rights_mask[to.0 as usize]
+                | self.castling_rights_mask[from.0 as usize];
+            if !new_state.castling.is_empty() && castle_mask != 0 {
+                let castle_zob_index = new_state.castling.update_castling_mask(castle_mask);
                 zob ^= z_castle(castle_zob_index);
             }
 
@@ -861,6 +1087,32 @@ impl Board {
         }
     }
 
+    /// Returns the UCI notation of a move, such as "e2e4" or "e7e8q".
+    ///
+    /// This is the same as `BitMove::stringify()`, except in Chess960 mode, where castling moves
+    /// are written as the king capturing its own rook.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    /// let board = Board::from_fen(fen).unwrap();
+    /// let castle = board.parse_san("O-O").unwrap();
+    /// assert_eq!(board.uci_move(castle), "e1g1");
+    ///
+    /// let board = Board::from_chess960_fen(fen).unwrap();
+    /// assert_eq!(board.uci_move(castle), "e1h1");
+    /// ```
+    pub fn uci_move(&self, mov: BitMove) -> String {
+        if self.chess960 && mov.is_castle() {
+            format!("{}{}", mov.get_src(), mov.get_dest())
+        } else {
+            mov.stringify()
+        }
+    }
+
     /// Applies a UCI move to the board. If the move is a valid string representing a UCI move, then
     /// true will be returned & the move will be applied. Otherwise, false is returned and the board isn't
     /// changed.
@@ -879,7 +1131,7 @@ impl Board {
         let all_moves: MoveList = self.generate_moves();
         let bit_move: Option<BitMove> = all_moves
             .iter()
-            .find(|m| m.stringify() == uci_move)
+            .find(|m| self.uci_move(**m) == uci_move)
             .cloned();
         if let Some(mov) = bit_move {
             self.apply_move(mov);
@@ -888,6 +1140,74 @@ impl Board {
         false
     }
 
+    /// Returns the Standard Algebraic Notation (SAN) of a legal move, such as "Nbd7", "exd6",
+    /// "e8=Q+" or "O-O-O#".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let board = Board::start_pos();
+    /// let mov = board.parse_san("Nf3").unwrap();
+    /// assert_eq!(board.san(mov), "Nf3");
+    /// assert_eq!(mov.stringify(), "g1f3");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// May panic if the move isn't legal for the current position.
+    pub fn san(&self, mov: BitMove) -> String {
+        PGNMove::from_bit_move(self, mov).to_string()
+    }
+
+    /// Parses a move in Standard Algebraic Notation (SAN) into the legal move it describes.
+    ///
+    /// Piece letters, file and rank disambiguation, captures, promotions, castling, and check or
+    /// checkmate suffixes are accepted. Some sloppy forms are accepted as well, such as castling
+    /// with zeros ("0-0"), redundant disambiguation ("Ng1f3"), a missing "=" for promotions
+    /// ("e8Q"), and an en-passant suffix ("exd6e.p.").
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let board = Board::start_pos();
+    /// assert!(board.parse_san("e4").is_ok());
+    /// assert!(board.parse_san("e5").is_err());
+    /// ```
+    pub fn parse_san(&self, san: &str) -> Result<BitMove, SanError> {
+        let pgn_move = PGNMove::parse(san.trim()).map_err(|_| SanError::Unparseable {
+            san: san.to_owned(),
+        })?;
+        pgn_move.to_bit_move(self)
+    }
+
+    /// Applies a move in Standard Algebraic Notation to the board. If the move is a legal SAN
+    /// move, then true will be returned & the move will be applied. Otherwise, false is returned
+    /// and the board isn't changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let mut board = Board::start_pos();
+    /// assert!(board.apply_san_move("e4"));
+    /// assert!(board.apply_san_move("e5"));
+    /// assert!(!board.apply_san_move("O-O"));
+    /// ```
+    pub fn apply_san_move(&mut self, san: &str) -> bool {
+        match self.parse_san(san) {
+            Ok(mov) => {
+                self.apply_move(mov);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
     /// Un-does the previously applied move, allowing the Board to return to it's most recently held state.
     ///
     /// # Panics
@@ -1048,11 +1368,13 @@ impl Board {
     /// unsafe { chessboard.undo_null_move(); }
     ///
     /// assert_eq!(chessboard.moves_played(), board_clone.moves_played());
+    /// assert_eq!(chessboard.depth(), board_clone.depth());
     /// assert_eq!(chessboard.fen(), board_clone.fen());
     /// ```
     pub unsafe fn undo_null_move(&mut self) {
         assert!(self.state.prev_move.is_null());
         self.turn = self.turn.other_player();
+        self.depth -= 1;
         self.state = self.state.get_prev().unwrap();
     }
 
@@ -1248,8 +1570,14 @@ impl Board {
             *to_r_orig = player.relative_square(SQ(2));
             *r_dst = player.relative_square(SQ(3));
         }
-        self.move_piece_c(Piece::make_lossy(player, PieceType::K), k_src, *to_r_orig);
-        self.move_piece_c(Piece::make_lossy(player, PieceType::R), *r_src, *r_dst);
+        // In Chess960 the king or rook might not move, or land on the other's square, so
+        // both are removed before being placed again.
+        let king = Piece::make_lossy(player, PieceType::K);
+        let rook = Piece::make_lossy(player, PieceType::R);
+        self.remove_piece_c(king, k_src);
+        self.remove_piece_c(rook, *r_src);
+        self.put_piece_c(king, *to_r_orig);
+        self.put_piece_c(rook, *r_dst);
     }
 
     /// Helper function to remove a Castling for a given player.
@@ -1268,8 +1596,120 @@ impl Board {
             player.relative_square(SQ(3))
         };
 
-        self.move_piece_c(Piece::make_lossy(player, PieceType::K), k_dst, k_src);
-        self.move_piece_c(Piece::make_lossy(player, PieceType::R), r_dst, r_src);
+        let king = Piece::make_lossy(player, PieceType::K);
+        let rook = Piece::make_lossy(player, PieceType::R);
+        self.remove_piece_c(king, k_dst);
+        self.remove_piece_c(rook, r_dst);
+        self.put_piece_c(king, k_src);
+        self.put_piece_c(rook, r_src);
+    }
+
+    /// Helper function to set the castling rights from the castling field of a FEN string.
+    ///
+    /// Accepts standard (`KQkq`), X-FEN and Shredder-FEN castling fields. `K` and `Q` refer to the
+    /// outermost rook on that side of the king, while a file letter refers to the rook on that file.
+    /// The board is switched to Chess960 mode if `chess960` is true, or if any castling right
+    /// involves a king or rook outside of its standard starting square.
+    ///
+    /// Assumes the pieces have already been placed on the board.
+    fn set_castling_rights(
+        &mut self,
+        castling: &str,
+        chess960: bool,
+    ) -> Result<Castling, FenBuildError> {
+        let err = || FenBuildError::UnrecognizedCastling {
+            castling: castling.to_string(),
+        };
+
+        let mut rights: Vec<(Player, CastleType, SQ, SQ)> = Vec::with_capacity(4);
+        for ch in castling.chars() {
+            if ch == '-' {
+                continue;
+            }
+            let player = if ch.is_ascii_uppercase() {
+                Player::White
+            } else {
+                Player::Black
+            };
+            let c = ch.to_ascii_lowercase();
+            match self.find_castling_rook(player, c) {
+                Some((side, k_sq, r_sq)) => rights.push((player, side, k_sq, r_sq)),
+                // Rights without a matching king and rook are kept as in standard chess
+                None if !chess960 && (c == 'k' || c == 'q') => {
+                    let side = if c == 'k' {
+                        CastleType::KingSide
+                    } else {
+                        CastleType::QueenSide
+                    };
+                    let k_sq = player.relative_square(SQ(WHITE_KING_START));
+                    let r_sq = SQ(CASTLING_ROOK_START[player as usize][side as usize]);
+                    rights.push((player, side, k_sq, r_sq));
+                }
+                None => return Err(err()),
+            }
+        }
+
+        self.chess960 = chess960
+            || rights.iter().any(|&(player, side, k_sq, r_sq)| {
+                k_sq != player.relative_square(SQ(WHITE_KING_START))
+                    || r_sq != SQ(CASTLING_ROOK_START[player as usize][side as usize])
+            });
+
+        let mut castle_bytes = Castling::empty();
+        for (player, side, k_sq, r_sq) in rights {
+            castle_bytes.add_castling_right(player, side);
+            self.set_castling_right(player, side, k_sq, r_sq);
+        }
+        Ok(castle_bytes)
+    }
+
+    /// Helper function to find the king and rook of a castling right, from a castling character
+    /// of a FEN string. Returns `None` if the player has no such castling rook.
+    fn find_castling_rook(&self, player: Player, c: char) -> Option<(CastleType, SQ, SQ)> {
+        let kings: BitBoard = self.piece_bb(player, PieceType::K);
+        if kings.count_bits() != 1 {
+            return None;
+        }
+        let k_sq: SQ = kings.to_sq();
+        if player.relative_rank_of_sq(k_sq) != Rank::R1 {
+            return None;
+        }
+        let rooks: BitBoard = self.piece_bb(player, PieceType::R) & k_sq.rank_bb();
+        let k_file = k_sq.file_idx_of_sq();
+        let back_rank = |file: u8| SQ((k_sq.0 & !0b111) | file);
+        let has_rook = |file: &u8| (rooks & back_rank(*file).to_bb()).is_not_empty();
+
+        let r_file: u8 = match c {
+            'k' => (k_file + 1..FILE_CNT as u8).rev().find(has_rook)?,
+            'q' => (0..k_file).find(has_rook)?,
+            'a'..='h' => c as u8 - b'a',
+            _ => return None,
+        };
+        if r_file == k_file || !has_rook(&r_file) {
+            return None;
+        }
+        let side = if r_file > k_file {
+            CastleType::KingSide
+        } else {
+            CastleType::QueenSide
+        };
+        Some((side, k_sq, back_rank(r_file)))
+    }
+
+    /// Helper function to record the king and rook involved with a single castling right.
+    fn set_castling_right(&mut self, player: Player, side: CastleType, k_sq: SQ, r_sq: SQ) {
+        let mask: u8 = Castling::castle_bit(player, side).bits();
+        self.castling_rights_mask[k_sq.0 as usize] |= mask;
+        self.castling_rights_mask[r_sq.0 as usize] |= mask;
+        self.castling_rook_sq[player as usize][side as usize] = r_sq;
+
+        let (k_to, r_to) = match side {
+            CastleType::KingSide => (player.relative_square(SQ(6)), player.relative_square(SQ(5))),
+            CastleType::QueenSide => (player.relative_square(SQ(2)), player.relative_square(SQ(3))),
+        };
+        self.castling_path[player as usize][side as usize] =
+            (between_bb(r_sq, r_to) | between_bb(k_sq, k_to) | r_to.to_bb() | k_to.to_bb())
+                & !(k_sq.to_bb() | r_sq.to_bb());
     }
 
     /// Outputs the Blockers of a given square.
@@ -1320,6 +1760,61 @@ impl Board {
         self.state.zobrist
     }
 
+    /// Return the Polyglot key of the board.
+    ///
+    /// Unlike [`Board::zobrist()`], this key is computed from the standard Polyglot Random64
+    /// table, and can be used to look up positions in Polyglot opening books. It is computed
+    /// from scratch, so it is much slower than [`Board::zobrist()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let chessboard = Board::start_pos();
+    /// assert_eq!(chessboard.polyglot_key(), 0x463b96181691fc9c);
+    /// ```
+    ///
+    /// [`Board::zobrist()`]: struct.Board.html#method.zobrist
+    pub fn polyglot_key(&self) -> u64 {
+        use self::polyglot::*;
+
+        let mut key: u64 = 0;
+        let mut occ = self.occupied();
+        while let Some(sq) = occ.pop_some_lsb() {
+            let (player, piece) = self.piece_at_sq(sq).player_piece_lossy();
+            let kind = 2 * (piece as usize - 1) + (player == Player::White) as usize;
+            key ^= RANDOM64[64 * kind + sq.0 as usize];
+        }
+
+        let castles = [
+            (Player::White, CastleType::KingSide),
+            (Player::White, CastleType::QueenSide),
+            (Player::Black, CastleType::KingSide),
+            (Player::Black, CastleType::QueenSide),
+        ];
+        for (i, &(player, side)) in castles.iter().enumerate() {
+            if self.can_castle(player, side) {
+                key ^= RANDOM64[RANDOM_CASTLE + i];
+            }
+        }
+
+        // The en-passant file only counts if a pawn is able to capture onto it
+        let ep_sq = self.ep_square();
+        if ep_sq != NO_SQ
+            && (pawn_attacks_from(ep_sq, self.turn.other_player())
+                & self.piece_bb(self.turn, PieceType::P))
+            .is_not_empty()
+        {
+            key ^= RANDOM64[RANDOM_EN_PASSANT + ep_sq.file_idx_of_sq() as usize];
+        }
+
+        if self.turn == Player::White {
+            key ^= RANDOM64[RANDOM_TURN];
+        }
+        key
+    }
+
     /// Return the pawn key of the board.
     ///
     /// This is a semi-unique key for any configuration of pawns on the board.
@@ -1680,19 +2175,28 @@ impl Board {
         self.state.castling.player_can_castle(player)
     }
 
+    /// Returns if the board follows Chess960 (Fischer Random Chess) castling rules.
+    ///
+    /// In Chess960 mode, FEN strings are written as X-FEN, and castling moves are written in UCI
+    /// notation as the king capturing its own rook.
+    #[inline(always)]
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
     /// Check if the castle path is impeded for the current player. Does not assume that the
     /// current player has the ability to castle, whether by having the castling-rights to, or
     /// having the rook and king be in the correct square.
     #[inline]
     pub fn castle_impeded(&self, castle_type: CastleType) -> bool {
-        let path: BitBoard = BitBoard(CASTLING_PATH[self.turn as usize][castle_type as usize]);
+        let path: BitBoard = self.castling_path[self.turn as usize][castle_type as usize];
         (path & self.occupied()).is_not_empty()
     }
 
     /// Square of the Rook that is involved with the current player's castle.
     #[inline]
     pub fn castling_rook_square(&self, castle_type: CastleType) -> SQ {
-        SQ(CASTLING_ROOK_START[self.turn as usize][castle_type as usize])
+        self.castling_rook_sq[self.turn as usize][castle_type as usize]
     }
 
     /// Return the last move played, if any.
@@ -1753,6 +2257,228 @@ impl Board {
         !self.in_check() && (self.state.rule_50 >= 50 || self.generate_moves().is_empty())
     }
 
+    /// Returns if the current position has occurred at least `count` times in the game, counting
+    /// the current occurrence.
+    ///
+    /// Only the positions since the last capture, pawn move, or null move are compared, as
+    /// earlier positions cannot repeat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let mut board = Board::start_pos();
+    /// for mov in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"].iter() {
+    ///     board.apply_uci_move(mov);
+    /// }
+    /// assert!(board.is_repetition(3));
+    /// assert!(!board.is_repetition(4));
+    /// ```
+    pub fn is_repetition(&self, count: u8) -> bool {
+        let mut occurrences: u8 = 1;
+        let mut state: &BoardState = &self.state;
+        let mut plies: u16 = 0;
+        while occurrences < count {
+            state = match state.reversible_prev() {
+                Some(prev) => prev,
+                None => return false,
+            };
+            plies += 1;
+            if plies % 2 == 0 && state.zobrist == self.state.zobrist {
+                occurrences += 1;
+            }
+        }
+        true
+    }
+
+    /// Returns if any position has occurred twice since the last capture, pawn move, or null
+    /// move. Unlike `Board::is_repetition`, the repeated position does not need to be the
+    /// current one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let mut board = Board::start_pos();
+    /// for mov in ["g1f3", "g8f6", "f3g1", "f6g8", "b1c3"].iter() {
+    ///     board.apply_uci_move(mov);
+    /// }
+    /// assert!(board.has_game_cycle());
+    /// assert!(!board.is_repetition(2));
+    /// ```
+    pub fn has_game_cycle(&self) -> bool {
+        let mut keys: Vec<u64> = vec![self.state.zobrist];
+        let mut state: &BoardState = &self.state;
+        while let Some(prev) = state.reversible_prev() {
+            state = prev;
+            keys.push(state.zobrist);
+        }
+        // Positions can only repeat with the same side to move
+        keys.iter()
+            .enumerate()
+            .any(|(i, key)| keys.iter().skip(i + 2).step_by(2).any(|other| other == key))
+    }
+
+    /// Returns if the current position should be scored as a draw by a search, `ply` plies from
+    /// the root of the search. This is the case after fifty moves without a capture or pawn move,
+    /// or if the position repeats one that occurred after the root. Positions from the root or
+    /// before it must have occurred twice already.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let mut board = Board::start_pos();
+    /// for mov in ["g1f3", "g8f6", "f3g1", "f6g8"].iter() {
+    ///     board.apply_uci_move(mov);
+    /// }
+    /// // The starting position repeats, but it was the root of a search four plies ago.
+    /// assert!(board.is_draw(5));
+    /// assert!(!board.is_draw(4));
+    /// ```
+    pub fn is_draw(&self, ply: u16) -> bool {
+        if self.state.rule_50 > 99 && (!self.in_check() || !self.generate_moves().is_empty()) {
+            return true;
+        }
+        let mut state: &BoardState = &self.state;
+        let mut plies: u16 = 0;
+        let mut repeated = false;
+        while let Some(prev) = state.reversible_prev() {
+            state = prev;
+            plies += 1;
+            if plies % 2 == 0 && state.zobrist == self.state.zobrist {
+                if plies < ply || repeated {
+                    return true;
+                }
+                repeated = true;
+            }
+        }
+        false
+    }
+
+    /// Returns if the side to move has a move that repeats an earlier position, `ply` plies from
+    /// the root of a search. Unlike `Board::is_draw`, this detects a repetition before it
+    /// occurs on the board, using a cuckoo table of reversible moves.
+    ///
+    /// Positions from before the root only count if they have already occurred twice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    ///
+    /// let mut board = Board::start_pos();
+    /// for mov in ["g1f3", "g8f6", "f3g1"].iter() {
+    ///     board.apply_uci_move(mov);
+    /// }
+    /// // Black can play f6g8 to repeat the starting position.
+    /// assert!(board.has_upcoming_repetition(4));
+    /// assert!(!board.has_upcoming_repetition(0));
+    /// ```
+    pub fn has_upcoming_repetition(&self, ply: u16) -> bool {
+        let mut state: &BoardState = match self.state.reversible_prev() {
+            Some(prev) => prev,
+            None => return false,
+        };
+        let mut plies: u16 = 1;
+        while let Some(prev) = state.reversible_prev().and_then(|s| s.reversible_prev()) {
+            state = prev;
+            plies += 2;
+            let mov = match cuckoo_move(self.state.zobrist ^ state.zobrist) {
+                Some(mov) => mov,
+                None => continue,
+            };
+            let (src, dst) = (mov.get_src(), mov.get_dest());
+            if (between_bb(src, dst) & self.occupied()).is_not_empty() {
+                continue;
+            }
+            if ply > plies {
+                return true;
+            }
+            // Before the root, the move must belong to the side to move. Both directions of a
+            // move share an entry, so check the square that is occupied.
+            let sq = if self.piece_at_sq(src) == Piece::None {
+                dst
+            } else {
+                src
+            };
+            if self.piece_at_sq(sq).player_lossy() == self.turn && state.is_repeated() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns if neither player has enough material to checkmate the other. This is the case
+    /// for King vs King, King and Bishop vs King, King and Knight vs King, and when all remaining
+    /// pieces besides the kings are bishops on squares of the same color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let pieces: BitBoard = self.occupied() & !self.piece_bb_both_players(PieceType::K);
+        if pieces.count_bits() <= 1 {
+            return (pieces & self.piece_two_bb_both_players(PieceType::B, PieceType::N)) == pieces;
+        }
+        let bishops: BitBoard = self.piece_bb_both_players(PieceType::B);
+        bishops == pieces
+            && ((bishops & BitBoard(DARK_SQUARES)).is_empty()
+                || (bishops & BitBoard(LIGHT_SQUARES)).is_empty())
+    }
+
+    /// Returns the outcome of the game if it has ended, or `None` otherwise.
+    ///
+    /// Checkmate and stalemate take priority over draws by insufficient material, the
+    /// seventy-five-move rule and fivefold repetition. Draws by the fifty-move rule and
+    /// threefold repetition are reported last, as they need to be claimed by a player. See
+    /// `Termination::is_automatic()`.
+    ///
+    /// This method can be computationally expensive, do not use outside of Engines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::{Board, Player};
+    /// use pleco::board::outcome::Termination;
+    ///
+    /// let mut board = Board::start_pos();
+    /// assert!(board.outcome().is_none());
+    ///
+    /// for mov in ["f2f3", "e7e5", "g2g4", "d8h4"].iter() {
+    ///     board.apply_uci_move(mov);
+    /// }
+    /// let outcome = board.outcome().unwrap();
+    /// assert_eq!(outcome.termination, Termination::Checkmate);
+    /// assert_eq!(outcome.winner, Some(Player::Black));
+    /// ```
+    pub fn outcome(&self) -> Option<Outcome> {
+        let termination = if self.generate_moves().is_empty() {
+            if self.in_check() {
+                return Some(Outcome {
+                    termination: Termination::Checkmate,
+                    winner: Some(!self.turn),
+                });
+            }
+            Termination::Stalemate
+        } else if self.is_insufficient_material() {
+            Termination::InsufficientMaterial
+        } else if self.state.rule_50 >= 150 {
+            Termination::SeventyFiveMoves
+        } else if self.is_repetition(5) {
+            Termination::FivefoldRepetition
+        } else if self.state.rule_50 >= 100 {
+            Termination::FiftyMoves
+        } else if self.is_repetition(3) {
+            Termination::ThreefoldRepetition
+        } else {
+            return None;
+        };
+        Some(Outcome {
+            termination,
+            winner: None,
+        })
+    }
+
     /// Return the `BitBoard` of all checks on the current player's king. If the current side
     /// to move is not in check, the `BitBoard` will be empty.
     #[inline(always)]
@@ -2379,6 +3105,7 @@ pub enum BoardError {
     IncorrectKingNum { player: Player, num: u8 },
     IncorrectKingSQ { player: Player, sq: SQ },
     BadEPSquare { sq: SQ },
+    OpponentInCheck { player: Player },
 }
 
 impl fmt::Debug for BoardError {
@@ -2393,6 +3120,9 @@ impl fmt::Debug for BoardError {
                 player, sq
             ),
             BoardError::BadEPSquare { sq } => writeln!(f, "Bad En-passant Square: {}", sq),
+            BoardError::OpponentInCheck { player } => {
+                writeln!(f, "{} is in check, but it is not their turn", player)
+            }
         }
     }
 }
@@ -2409,11 +3139,11 @@ impl Board {
     /// Checks if the current state of the Board is okay.
     pub fn is_okay(&self) -> Result<(), BoardError> {
         self.check_king()?;
+        self.check_opponent_check()?;
         Ok(())
     }
 
     fn check_king(&self) -> Result<(), BoardError> {
-        // TODO: Implement attacks to opposing king must be zero
         let w_king_num = self.count_piece(Player::White, PieceType::K);
         let b_king_num = self.count_piece(Player::Black, PieceType::K);
         if w_king_num != 1 {
@@ -2422,7 +3152,7 @@ impl Board {
                 num: w_king_num,
             });
         }
-        if w_king_num != 1 {
+        if b_king_num != 1 {
             return Err(BoardError::IncorrectKingNum {
                 player: Player::Black,
                 num: b_king_num,
@@ -2445,6 +3175,17 @@ impl Board {
 
         Ok(())
     }
+
+    // The player who just moved can never be left in check.
+    fn check_opponent_check(&self) -> Result<(), BoardError> {
+        let them = !self.turn;
+        let attackers = self.attackers_to(self.king_sq(them), self.occupied())
+            & self.get_occupied_player(self.turn);
+        if attackers.is_not_empty() {
+            return Err(BoardError::OpponentInCheck { player: them });
+        }
+        Ok(())
+    }
     //
     //    fn check_bitboards(&self) -> bool {
     //        assert_eq!(self.occupied_white() & self.occupied_black(), BitBoard(0));
@@ -2764,6 +3505,172 @@ mod tests {
         assert!(!b.apply_uci_move("a1a5"));
     }
 
+    #[test]
+    fn san_move() {
+        let b = Board::from_fen("r3k2r/1P6/8/3pP3/8/5N2/8/R3K1NR w KQkq d6 0 1").unwrap();
+        let parse = |san: &str| b.parse_san(san).map(|m| m.stringify());
+        assert_eq!(parse("Nf3d2").unwrap(), "f3d2");
+        assert_eq!(parse("Ngh3").unwrap(), "g1h3");
+        assert_eq!(parse("exd6e.p.").unwrap(), "e5d6");
+        assert_eq!(parse("exd6").unwrap(), "e5d6");
+        assert_eq!(parse("bxa8=Q+").unwrap(), "b7a8q");
+        assert_eq!(parse("bxa8N").unwrap(), "b7a8n");
+        assert_eq!(parse("0-0-0").unwrap(), "e1c1");
+        assert_eq!(parse("O-O-O").unwrap(), "e1c1");
+        assert!(parse("O-O").is_err());
+        assert!(parse("b8").is_err());
+        assert!(parse("Xe4").is_err());
+
+        for mov in b.generate_moves().iter() {
+            assert_eq!(b.parse_san(&b.san(*mov)).unwrap(), *mov);
+        }
+        let promo = b.parse_san("b8=Q").unwrap();
+        assert_eq!(b.san(promo), "b8=Q+");
+    }
+
+    #[test]
+    fn chess960_castling() {
+        let castles = [
+            (
+                "4k3/8/8/8/8/8/8/6KR w H - 0 1",
+                "g1h1",
+                "4k3/8/8/8/8/8/8/5RK1 b - - 1 1",
+            ),
+            (
+                "4k3/8/8/8/8/8/8/1RK5 w B - 0 1",
+                "c1b1",
+                "4k3/8/8/8/8/8/8/2KR4 b - - 1 1",
+            ),
+            (
+                "4k3/8/8/8/8/8/8/2RK4 w C - 0 1",
+                "d1c1",
+                "4k3/8/8/8/8/8/8/2KR4 b - - 1 1",
+            ),
+            (
+                "1r2k1r1/8/8/8/8/8/8/1R2K1R1 w KQkq - 0 1",
+                "e1b1",
+                "1r2k1r1/8/8/8/8/8/8/2KR2R1 b kq - 1 1",
+            ),
+        ];
+        for &(fen, uci, after) in castles.iter() {
+            let mut b = Board::from_fen(fen).unwrap();
+            assert!(b.is_chess960());
+            let zobrist = b.zobrist();
+            assert!(b.apply_uci_move(uci), "{}", fen);
+            assert!(b.last_move().unwrap().is_castle());
+            assert_eq!(b.fen(), after);
+            assert_eq!(b.zobrist(), Board::from_fen(after).unwrap().zobrist());
+            b.undo_move();
+            assert_eq!(b.fen(), Board::from_fen(fen).unwrap().fen());
+            assert_eq!(b.zobrist(), zobrist);
+        }
+
+        // An enemy rook behind the castling rook keeps the king from castling
+        let b = Board::from_fen("4k3/8/8/8/8/8/8/rRK5 w B - 0 1").unwrap();
+        assert!(b.generate_moves().iter().all(|m| !m.is_castle()));
+
+        // Standard castling is written differently in Chess960 mode
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert!(!Board::from_fen(fen).unwrap().is_chess960());
+        assert!(Board::from_fen(fen).unwrap().apply_uci_move("e1g1"));
+        assert!(Board::from_chess960_fen(fen)
+            .unwrap()
+            .apply_uci_move("e1h1"));
+        assert!(!Board::from_chess960_fen(fen)
+            .unwrap()
+            .apply_uci_move("e1g1"));
+        assert_eq!(
+            Board::from_chess960_fen(fen).unwrap().shredder_fen(),
+            "r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1"
+        );
+
+        assert!(Board::from_chess960_fen("4k3/8/8/8/8/8/8/4K3 w K - 0 1").is_err());
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w E - 0 1").is_err());
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w X - 0 1").is_err());
+    }
+
+    #[test]
+    fn repetitions() {
+        let mut b = Board::start_pos();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        assert!(b.is_repetition(1));
+        assert!(!b.is_repetition(2));
+        for _ in 0..2 {
+            for mov in shuffle.iter() {
+                assert!(b.apply_uci_move(mov));
+            }
+        }
+        assert!(b.is_repetition(3));
+        assert!(!b.is_repetition(4));
+        assert!(b.has_game_cycle());
+
+        // A pawn move makes earlier positions unreachable
+        assert!(b.apply_uci_move("e2e4"));
+        assert!(!b.is_repetition(2));
+        assert!(!b.has_game_cycle());
+        let black_shuffle = ["g8f6", "g1f3", "f6g8", "f3g1"];
+        for mov in black_shuffle.iter() {
+            assert!(b.apply_uci_move(mov));
+        }
+        assert!(b.is_repetition(2));
+        assert!(!b.is_repetition(3));
+
+        // Repetitions are still found after a shallow clone
+        let mut clone = b.shallow_clone();
+        for mov in black_shuffle.iter() {
+            assert!(clone.apply_uci_move(mov));
+        }
+        assert!(clone.is_repetition(3));
+
+        // Null moves also end the search for repetitions
+        let mut b = Board::start_pos();
+        for mov in shuffle.iter() {
+            assert!(b.apply_uci_move(mov));
+        }
+        unsafe {
+            b.apply_null_move();
+        }
+        assert!(!b.has_game_cycle());
+    }
+
+    #[test]
+    fn search_draws() {
+        let b = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 100 80").unwrap();
+        assert!(b.is_draw(0));
+        let b = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 99 80").unwrap();
+        assert!(!b.is_draw(0));
+
+        let mut b = Board::start_pos();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for mov in shuffle.iter().chain(shuffle.iter()) {
+            assert!(b.apply_uci_move(mov));
+        }
+        assert!(b.is_draw(0));
+
+        // The starting position has occurred twice, so f6g8 would repeat it a third time
+        let mut b = Board::start_pos();
+        for mov in shuffle.iter().chain(shuffle[0..3].iter()) {
+            assert!(b.apply_uci_move(mov));
+        }
+        assert!(!b.is_draw(0));
+        assert!(b.has_upcoming_repetition(0));
+
+        // Afterwards, no single move reaches an earlier position
+        assert!(b.apply_uci_move("b8c6"));
+        assert!(!b.has_upcoming_repetition(0));
+    }
+
+    #[test]
+    fn board_validity() {
+        assert!(Board::start_pos().is_okay().is_ok());
+        let b = Board::from_fen("4k3/4R3/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(b.is_okay().is_ok());
+        let b = Board::from_fen("4k3/4R3/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(b.is_okay().is_err());
+        assert!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/4KK2 w - - 0 1").is_err());
+    }
+
     #[test]
     fn check_state() {
         let b = Board::start_pos();
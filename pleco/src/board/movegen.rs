@@ -384,7 +384,7 @@ where
             let k_to = P::player().relative_square(if king_side { SQ::G1 } else { SQ::C1 });
 
             let enemies: BitBoard = self.them_occ;
-            let direction: fn(SQ) -> SQ = if king_side {
+            let direction: fn(SQ) -> SQ = if k_to > ksq {
                 |x: SQ| x - SQ(1)
             } else {
                 |x: SQ| x + SQ(1)
@@ -403,6 +403,16 @@ where
                 }
                 s = direction(s);
             }
+
+            // In Chess960, the castling rook might have been shielding the king's destination
+            // from an enemy rook or queen on the back rank.
+            if can_castle && self.board.is_chess960() {
+                let attackers: BitBoard = rook_moves(self.occ ^ r_from.to_bb(), k_to)
+                    & self
+                        .board
+                        .piece_two_bb(PieceType::R, PieceType::Q, !P::player());
+                can_castle = attackers.is_empty();
+            }
             if can_castle {
                 self.check_and_add::<L>(BitMove::init(PreMoveInfo {
                     src: ksq,
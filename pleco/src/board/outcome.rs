@@ -0,0 +1,177 @@
+//! Contains the `Outcome` and `Termination` structures, describing how and why a game of chess
+//! has ended.
+//!
+//! The outcome of a [`Board`] is found through [`Board::outcome()`].
+//!
+//! [`Board`]: ../struct.Board.html
+//! [`Board::outcome()`]: ../struct.Board.html#method.outcome
+
+use std::fmt;
+
+use super::pgn::GameResult;
+use core::Player;
+
+/// The reason a game has ended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Termination {
+    /// The side to move is in check and has no legal moves.
+    Checkmate,
+    /// The side to move is not in check, but has no legal moves.
+    Stalemate,
+    /// Neither player has enough material left to checkmate.
+    InsufficientMaterial,
+    /// 75 moves have been played by each player without a capture or pawn move.
+    SeventyFiveMoves,
+    /// The same position has occurred five times.
+    FivefoldRepetition,
+    /// 50 moves have been played by each player without a capture or pawn move. The game is
+    /// only drawn if a player claims it.
+    FiftyMoves,
+    /// The same position has occurred three times. The game is only drawn if a player
+    /// claims it.
+    ThreefoldRepetition,
+}
+
+impl Termination {
+    /// Returns if the game ends automatically, rather than needing a player to claim a draw.
+    pub fn is_automatic(self) -> bool {
+        match self {
+            Termination::FiftyMoves | Termination::ThreefoldRepetition => false,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient material",
+            Termination::SeventyFiveMoves => "seventy-five-move rule",
+            Termination::FivefoldRepetition => "fivefold repetition",
+            Termination::FiftyMoves => "fifty-move rule",
+            Termination::ThreefoldRepetition => "threefold repetition",
+        };
+        f.pad(s)
+    }
+}
+
+/// The outcome of a finished game: how it ended, and which player won, if any.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Outcome {
+    /// The reason the game has ended.
+    pub termination: Termination,
+    /// The winner of the game, or `None` if the game is drawn.
+    pub winner: Option<Player>,
+}
+
+impl Outcome {
+    /// Returns if the game is a draw.
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none()
+    }
+
+    /// Returns the PGN result of the game.
+    pub fn result(&self) -> GameResult {
+        match self.winner {
+            Some(Player::White) => GameResult::WhiteWins,
+            Some(Player::Black) => GameResult::BlackWins,
+            None => GameResult::Draw,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} by {}", self.result(), self.termination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Board;
+
+    fn outcome(fen: &str) -> Option<Outcome> {
+        Board::from_fen(fen).unwrap().outcome()
+    }
+
+    #[test]
+    fn terminations() {
+        let mate = outcome("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(mate.termination, Termination::Checkmate);
+        assert_eq!(mate.winner, Some(Player::White));
+        assert_eq!(mate.result(), GameResult::WhiteWins);
+
+        let stalemate = outcome("7k/8/6QK/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(stalemate.termination, Termination::Stalemate);
+        assert!(stalemate.is_draw());
+
+        let fifty = outcome("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert_eq!(fifty.termination, Termination::FiftyMoves);
+        assert!(!fifty.termination.is_automatic());
+        let seventy_five = outcome("4k3/8/8/8/8/8/8/R3K3 w - - 150 80").unwrap();
+        assert_eq!(seventy_five.termination, Termination::SeventyFiveMoves);
+        assert!(outcome("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").is_none());
+
+        // Checkmate takes priority over the move counters
+        let late_mate = outcome("7k/6Q1/6K1/8/8/8/8/8 b - - 150 80").unwrap();
+        assert_eq!(late_mate.termination, Termination::Checkmate);
+
+        assert!(Board::start_pos().outcome().is_none());
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let insufficient = [
+            "8/8/4k3/8/8/3K4/8/8 w - - 0 1",
+            "8/8/4k3/8/8/3K4/3B4/8 w - - 0 1",
+            "8/8/4k3/8/8/3K4/3N4/8 b - - 0 1",
+            "8/4b3/4k3/8/8/3K4/3B4/8 w - - 0 1",
+            "8/2b1b3/4k3/8/8/3K4/1B1B4/8 w - - 0 1",
+        ];
+        for fen in insufficient.iter() {
+            let b = Board::from_fen(fen).unwrap();
+            assert!(b.is_insufficient_material(), "{}", fen);
+            assert_eq!(
+                b.outcome().unwrap().termination,
+                Termination::InsufficientMaterial
+            );
+        }
+
+        let sufficient = [
+            "8/8/4k3/8/8/3K4/3P4/8 w - - 0 1",
+            "8/8/4k3/8/8/3K4/3R4/8 w - - 0 1",
+            "8/3b4/4k3/8/8/3K4/3B4/8 w - - 0 1",
+            "8/4n3/4k3/8/8/3K4/4B3/8 w - - 0 1",
+            "8/8/4k3/8/8/3K4/3NN3/8 w - - 0 1",
+        ];
+        for fen in sufficient.iter() {
+            assert!(
+                !Board::from_fen(fen).unwrap().is_insufficient_material(),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn repetitions() {
+        let mut b = Board::start_pos();
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        for mov in shuffle.iter().chain(shuffle.iter()) {
+            assert!(b.apply_uci_move(mov));
+        }
+        assert_eq!(
+            b.outcome().unwrap().termination,
+            Termination::ThreefoldRepetition
+        );
+        for mov in shuffle.iter().chain(shuffle.iter()) {
+            assert!(b.apply_uci_move(mov));
+        }
+        let fivefold = b.outcome().unwrap();
+        assert_eq!(fivefold.termination, Termination::FivefoldRepetition);
+        assert_eq!(fivefold.to_string(), "1/2-1/2 by fivefold repetition");
+    }
+}
@@ -244,4 +244,51 @@ mod tests {
         assert_eq!(3_894_594, perft(&b, 4));
     }
 
+    #[test]
+    fn perft_chess960() {
+        // Positions from the published Chess960 perft results, in Shredder-FEN
+        let positions: [(&str, [u64; 4]); 6] = [
+            (
+                "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+                [21, 528, 12_189, 326_672],
+            ),
+            (
+                "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+                [21, 807, 18_002, 667_366],
+            ),
+            (
+                "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+                [20, 479, 10_471, 273_318],
+            ),
+            (
+                "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+                [22, 593, 13_440, 382_958],
+            ),
+            (
+                "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+                [28, 1_120, 31_058, 1_171_749],
+            ),
+            (
+                "1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9",
+                [29, 502, 14_569, 287_739],
+            ),
+        ];
+        for &(fen, ref nodes) in positions.iter() {
+            let b: Board = Board::from_fen(fen).unwrap();
+            assert!(b.is_chess960());
+            assert_eq!(b.shredder_fen(), fen);
+            for (depth, &count) in nodes.iter().enumerate() {
+                assert_eq!(perft_all(&b, depth as u16 + 1).nodes, count, "{}", fen);
+            }
+        }
+    }
+
+    #[test]
+    fn perft_chess960_start_pos() {
+        // Start position 518 is the standard chess start position
+        let b: Board = Board::chess960_start_pos(518);
+        assert_eq!(b.fen(), Board::start_pos().fen());
+        perft_all(&b, 4).check(197_281, 1576, 0, 0, 0, 469, 8);
+    }
+
 }
@@ -1,35 +1,108 @@
-//! Module for generating chess boards from PGN notation.
+//! Module for generating chess boards from PGN (Portable Game Notation), and exporting
+//! the games played on a board back to PGN.
+//!
+//! A PGN game consists of a section of tag pairs, followed by the movetext. The movetext
+//! contains the moves of the game in Standard Algebraic Notation (SAN), optionally
+//! interleaved with comments, Numeric Annotation Glyphs (NAGs) and recursive variations,
+//! and is ended by a game termination marker.
+//!
+//! ```md,ignore
+//! [Event "F/S Return Match"]
+//! [Site "Belgrade, Serbia JUG"]
+//! [Date "1992.11.04"]
+//! [Round "29"]
+//! [White "Fischer, Robert J."]
+//! [Black "Spassky, Boris V."]
+//! [Result "1/2-1/2"]
+//!
+//! 1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+//! 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+//! ...
+//! 40. Rd6 Kc5 41. Ra6 Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+//! ```
+//!
+//! Parsing a [`PGN`] only checks the syntax of the game. To check the legality of the
+//! moves, the mainline can be replayed on a [`Board`] with [`PGN::replay`].
+//!
+//! # Examples
+//!
+//! ```
+//! use pleco::board::pgn::PGN;
+//!
+//! let pgn = PGN::parse("[White \"Anderssen\"]\n\n1. e4 e5 2. Nf3 (2. f4 exf4) 2... Nc6 *").unwrap();
+//! assert_eq!(pgn.tags.white, "Anderssen");
+//!
+//! let (board, moves) = pgn.replay().unwrap();
+//! assert_eq!(moves.len(), 4);
+//! assert_eq!(board.moves_played(), 4);
+//! ```
+//!
+//! See the [PGN Specification](https://www.chessclub.com/user/help/PGN-spec) for more information.
+//!
+//! [`PGN`]: struct.PGN.html
+//! [`PGN::replay`]: struct.PGN.html#method.replay
+//! [`Board`]: ../struct.Board.html
 
-//use super::Board;
+use super::{Board, FenBuildError, SanError};
+use core::piece_move::BitMove;
 use core::sq::SQ;
-use core::{File, PieceType, Rank};
+use core::{File, PieceType, Rank, ALL_FILES, ALL_RANKS};
 use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
 
-//[Event "F/S Return Match"]
-//[Site "Belgrade, Serbia JUG"]
-//[Date "1992.11.04"]
-//[Round "29"]
-//[White "Fischer, Robert J."]
-//[Black "Spassky, Boris V."]
-//[Result "1/2-1/2"]
-
-// 1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3
-// O-O 9. h3 Nb8 10. d4 Nbd7 11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15.
-// Nb1 h6 16. Bh4 c5 17. dxe5 Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21.
-// Nc4 Nxc4 22. Bxc4 Nb6 23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7
-// 27. Qe3 Qg5 28. Qxg5 hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33.
-// f3 Bc8 34. Kf2 Bf5 35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5
-// 40. Rd6 Kc5 41. Ra6 Nf2 42. g4 Bd3 43. Re6 1/2-1/2
-
-// https://www.chessclub.com/user/help/PGN-spec
-
+/// The result of a game, as given by the `Result` tag or the game termination marker.
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum GameResult {
+    /// "1-0"
     WhiteWins,
+    /// "0-1"
     BlackWins,
+    /// "1/2-1/2"
     Draw,
+    /// "*", the game is still in progress, abandoned or the result is unknown.
     Other,
 }
 
+impl Default for GameResult {
+    fn default() -> Self {
+        GameResult::Other
+    }
+}
+
+impl GameResult {
+    /// Parses a game termination marker. Returns `None` if the token is not one of
+    /// "1-0", "0-1", "1/2-1/2" or "*".
+    pub fn parse(token: &str) -> Option<GameResult> {
+        match token {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Other),
+            _ => None,
+        }
+    }
+
+    /// Returns the game termination marker of the result.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Other => "*",
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// The date a game was played on, as given by the `Date` tag. Unknown parts of the date
+/// are marked with question marks, e.g. "1992.??.??".
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ChessDate {
     Unknown,
     Year(u16),
@@ -37,93 +110,59 @@ pub enum ChessDate {
     Full(u16, u8, u8),
 }
 
+impl Default for ChessDate {
+    fn default() -> Self {
+        ChessDate::Unknown
+    }
+}
+
 impl ChessDate {
+    /// Parses the value of a `Date` tag.
     pub fn parse_chess_date(date: &str) -> Self {
-        let mut args = date[1..(date.len() - 1)].split('.');
-
-        let y = args.next().map(|m: &str| m.parse::<u16>());
-
-        if y.is_none() {
-            return ChessDate::Unknown;
-        }
-        let y_err = y.unwrap();
-        if y_err.is_err() {
-            return ChessDate::Unknown;
-        }
-        let year = y_err.unwrap();
+        let mut args = date.split('.');
 
-        let m = args.next().map(|m: &str| m.parse::<u8>());
+        let year = match args.next().map(|y: &str| y.parse::<u16>()) {
+            Some(Ok(year)) => year,
+            _ => return ChessDate::Unknown,
+        };
 
-        if m.is_none() {
-            return ChessDate::Year(year);
-        }
-        let m_err = m.unwrap();
-        if m_err.is_err() {
-            return ChessDate::Year(year);
-        }
-        let month = m_err.unwrap();
+        let month = match args.next().map(|m: &str| m.parse::<u8>()) {
+            Some(Ok(month)) => month,
+            _ => return ChessDate::Year(year),
+        };
 
-        let d = args.next().map(|m: &str| m.parse::<u8>());
-
-        if d.is_none() {
-            return ChessDate::YearMonth(year, month);
+        match args.next().map(|d: &str| d.parse::<u8>()) {
+            Some(Ok(day)) => ChessDate::Full(year, month, day),
+            _ => ChessDate::YearMonth(year, month),
         }
-
-        let d_err = d.unwrap();
-        if d_err.is_err() {
-            return ChessDate::YearMonth(year, month);
-        }
-        let day = d_err.unwrap();
-        ChessDate::Full(year, month, day)
     }
+}
 
-    pub fn to_string(&self) -> String {
+/// Formats the value of the `Date` tag, in the "YYYY.MM.DD" format.
+impl fmt::Display for ChessDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            ChessDate::Unknown => ("\"??\"").to_owned(),
-            ChessDate::Year(y) => {
-                let mut s = ("\"").to_owned();
-                s.push_str(y.to_string().as_ref());
-                s.push_str(".??.??\"");
-                s
-            }
-            ChessDate::YearMonth(y, m) => {
-                let mut s = ("\"").to_owned();
-                s.push_str(y.to_string().as_ref());
-                s.push('.');
-                s.push_str(m.to_string().as_ref());
-                s.push_str(".??\"");
-                s
-            }
-            ChessDate::Full(y, m, d) => {
-                let mut s = ("\"").to_owned();
-                s.push_str(y.to_string().as_ref());
-                s.push('.');
-                s.push_str(m.to_string().as_ref());
-                s.push('.');
-                s.push_str(d.to_string().as_ref());
-                s.push('"');
-                s
-            }
+            ChessDate::Unknown => write!(f, "????.??.??"),
+            ChessDate::Year(y) => write!(f, "{:04}.??.??", y),
+            ChessDate::YearMonth(y, m) => write!(f, "{:04}.{:02}.??", y, m),
+            ChessDate::Full(y, m, d) => write!(f, "{:04}.{:02}.{:02}", y, m, d),
         }
     }
 }
 
+/// The round of a game, as given by the `Round` tag. A round may contain multiple
+/// levels, e.g. "3.1". An empty round is displayed as "?".
+#[derive(Clone, PartialEq, Debug, Default)]
 pub struct ChessRound {
     rounds: Vec<u32>,
 }
 
-impl Default for ChessRound {
-    fn default() -> ChessRound {
-        ChessRound { rounds: Vec::new() }
-    }
-}
-
 impl ChessRound {
+    /// Parses the value of a `Round` tag.
     pub fn parse_chess_round(round: &str) -> ChessRound {
         let mut cr = ChessRound::default();
-        let args = round[1..(round.len() - 1)].split('.');
+        let args = round.split('.');
         args.for_each(|r: &str| {
-            //            r.parse().map(|m: u32| cr.rounds.push(m));
             if let Ok(m) = r.parse() {
                 cr.rounds.push(m)
             }
@@ -131,79 +170,92 @@ impl ChessRound {
         cr
     }
 
-    pub fn to_string(&self) -> String {
-        let mut s = "\"".to_string();
+    /// Returns each level of the round.
+    pub fn rounds(&self) -> &[u32] {
+        &self.rounds
+    }
+}
+
+/// Formats the value of the `Round` tag.
+impl fmt::Display for ChessRound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.rounds.is_empty() {
+            return write!(f, "?");
+        }
         for (i, x) in self.rounds.iter().enumerate() {
-            s.push_str(x.to_string().as_ref());
-            if i != self.rounds.len() - 1 {
-                s.push('.');
+            if i != 0 {
+                write!(f, ".")?;
             }
+            write!(f, "{}", x)?;
         }
-        s.push('"');
-        s
+        Ok(())
     }
 }
 
+/// The tag pairs of a PGN game.
+///
+/// The Seven Tag Roster (`Event`, `Site`, `Date`, `Round`, `White`, `Black` and `Result`) is
+/// stored in it's own fields, while any other tags are kept in the order they were given.
+#[derive(Clone, PartialEq, Debug)]
 pub struct PGNTags {
-    event: String,
-    site: String,
-    date: ChessDate,
-    round: ChessRound,
-    white: String,
-    black: String,
-    result: String,
+    /// The name of the tournament or match.
+    pub event: String,
+    /// The location of the event.
+    pub site: String,
+    /// The starting date of the game.
+    pub date: ChessDate,
+    /// The playing round of the game.
+    pub round: ChessRound,
+    /// The player of the white pieces.
+    pub white: String,
+    /// The player of the black pieces.
+    pub black: String,
+    /// The result of the game.
+    pub result: GameResult,
+    /// Any tags outside of the Seven Tag Roster, as (name, value) pairs.
+    pub extra: Vec<(String, String)>,
 }
 
+/// Displays the tags as tag pairs, one per line. The Seven Tag Roster is output first,
+/// followed by any extra tags.
 impl fmt::Display for PGNTags {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let s = self.to_string();
+        let mut s = String::new();
+        push_tag(&mut s, "Event", value_or_unknown(&self.event));
+        push_tag(&mut s, "Site", value_or_unknown(&self.site));
+        push_tag(&mut s, "Date", &self.date.to_string());
+        push_tag(&mut s, "Round", &self.round.to_string());
+        push_tag(&mut s, "White", value_or_unknown(&self.white));
+        push_tag(&mut s, "Black", value_or_unknown(&self.black));
+        push_tag(&mut s, "Result", self.result.as_str());
+        for &(ref tag, ref value) in self.extra.iter() {
+            push_tag(&mut s, tag, value);
+        }
         f.pad(&s)
     }
 }
 
 impl PGNTags {
-    pub fn to_string(&self) -> String {
-        let mut s: String = "[Event ".to_owned();
-        s.push_str(self.event.as_ref());
-        s.push_str("]\n[Site ");
-        s.push_str(self.site.as_ref());
-        s.push_str("]\n[Date ");
-        s.push_str(self.date.to_string().as_ref());
-        s.push_str("]\n[Round ");
-        s.push_str(self.round.to_string().as_ref());
-        s.push_str("]\n[White ");
-        s.push_str(self.white.as_ref());
-        s.push_str("]\n[Black ");
-        s.push_str(self.black.as_ref());
-        s.push_str("]\n[Result ");
-        s.push_str(self.result.as_ref());
-        s.push_str("]\n");
-        s
-    }
-
-    pub fn add(mut self, input: &str) -> Result<PGNTags, PGNError> {
-        let first_char = input.chars().nth(0).ok_or(PGNError::TagParse)?;
-        let last_char = input.chars().last().ok_or(PGNError::TagParse)?;
-        if input.len() < 3 || first_char != '[' || last_char != ']' {
+    /// Adds a tag pair from a line, such as `[White "Fischer, Robert J."]`.
+    pub fn add_tag(self, input: &str) -> Result<PGNTags, PGNError> {
+        let mut parser = Parser::new(input.trim());
+        parser.skip_whitespace();
+        if parser.peek() != Some('[') {
             return Err(PGNError::TagParse);
         }
-        let r = &input[1..(input.len() - 1)];
-        let quote_first = r.find('"').ok_or(PGNError::TagParse)?;
-        let quote_second = r.rfind('"').ok_or(PGNError::TagParse)?;
-        if quote_first >= quote_second - 2 {
+        let (tag, data) = parser.parse_tag_pair()?;
+        parser.skip_whitespace();
+        if parser.peek().is_some() {
             return Err(PGNError::TagParse);
         }
-        let in_quote = r[(quote_first)..(quote_second + 1)].to_owned();
-        let no_quote = r[..(quote_first - 1)]
-            .trim()
-            .split_whitespace()
-            .next()
-            .ok_or(PGNError::TagParse)?;
-        self = self.parse_tag(no_quote, in_quote)?;
-        Ok(self)
+        self.parse_tag(tag.as_ref(), data)
     }
 
+    /// Sets the value of a tag.
     pub fn parse_tag(mut self, tag: &str, data: String) -> Result<PGNTags, PGNError> {
+        if tag.is_empty() {
+            return Err(PGNError::TagParse);
+        }
         match tag {
             "Event" => self.event = data,
             "Site" => self.site = data,
@@ -211,11 +263,27 @@ impl PGNTags {
             "Round" => self.round = ChessRound::parse_chess_round(data.as_ref()),
             "White" => self.white = data,
             "Black" => self.black = data,
-            "Result" => self.result = data,
-            _ => return Err(PGNError::TagParse),
+            "Result" => self.result = GameResult::parse(data.as_ref()).unwrap_or_default(),
+            _ => match self.extra.iter().position(|&(ref t, _)| t == tag) {
+                Some(i) => self.extra[i].1 = data,
+                None => self.extra.push((tag.to_owned(), data)),
+            },
         }
         Ok(self)
     }
+
+    /// Returns the value of a tag outside of the Seven Tag Roster, if it exists.
+    pub fn get(&self, tag: &str) -> Option<&str> {
+        self.extra
+            .iter()
+            .find(|&&(ref t, _)| t == tag)
+            .map(|&(_, ref v)| v.as_ref())
+    }
+
+    /// Returns the starting position of the game, as given by the `FEN` tag.
+    pub fn fen(&self) -> Option<&str> {
+        self.get("FEN")
+    }
 }
 
 impl Default for PGNTags {
@@ -227,11 +295,35 @@ impl Default for PGNTags {
             round: ChessRound::default(),
             white: String::new(),
             black: String::new(),
-            result: String::new(),
+            result: GameResult::Other,
+            extra: Vec::new(),
+        }
+    }
+}
+
+fn value_or_unknown(value: &str) -> &str {
+    if value.is_empty() {
+        "?"
+    } else {
+        value
+    }
+}
+
+fn push_tag(s: &mut String, tag: &str, value: &str) {
+    s.push('[');
+    s.push_str(tag);
+    s.push_str(" \"");
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            s.push('\\');
         }
+        s.push(c);
     }
+    s.push_str("\"]\n");
 }
 
+/// Move suffix annotations.
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum PGNMoveTag {
     None,        // ''
     Good,        // '!'
@@ -242,18 +334,57 @@ pub enum PGNMoveTag {
     Doubtful,    // '?!'
 }
 
+impl PGNMoveTag {
+    fn parse(input: &str) -> Option<PGNMoveTag> {
+        match input {
+            "" => Some(PGNMoveTag::None),
+            "!" => Some(PGNMoveTag::Good),
+            "!!" => Some(PGNMoveTag::Excellent),
+            "?" => Some(PGNMoveTag::Bad),
+            "??" => Some(PGNMoveTag::Blunder),
+            "!?" => Some(PGNMoveTag::Interesting),
+            "?!" => Some(PGNMoveTag::Doubtful),
+            _ => None,
+        }
+    }
+
+    /// Returns the Numeric Annotation Glyph equivalent to the suffix annotation, if any.
+    pub fn nag(self) -> Option<u8> {
+        match self {
+            PGNMoveTag::None => None,
+            PGNMoveTag::Good => Some(1),
+            PGNMoveTag::Bad => Some(2),
+            PGNMoveTag::Excellent => Some(3),
+            PGNMoveTag::Blunder => Some(4),
+            PGNMoveTag::Interesting => Some(5),
+            PGNMoveTag::Doubtful => Some(6),
+        }
+    }
+}
+
 // Check = +
 // Checkmate = #
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum CheckType {
     Check,
     CheckMate,
 }
 
 // (File) OR (Rank) OR (Square)
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct PGNMoveSpecifier {
-    rank: Option<Rank>,
-    file: Option<File>,
-    square: Option<SQ>,
+    pub rank: Option<Rank>,
+    pub file: Option<File>,
+    pub square: Option<SQ>,
+}
+
+impl PGNMoveSpecifier {
+    /// Returns if a source square matches the specifier.
+    pub fn matches(&self, src: SQ) -> bool {
+        self.square.map_or(true, |sq| sq == src)
+            && self.file.map_or(true, |f| f == src.file())
+            && self.rank.map_or(true, |r| r == src.rank())
+    }
 }
 
 // [Piece](specifier)("capture")["dest"]("Promo")
@@ -261,69 +392,923 @@ pub struct PGNMoveSpecifier {
 // (specifier) => rank or file or square if needed
 // (capture) => x if capture
 //
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct PGNRegMove {
-    piece: Option<PieceType>,
-    specifier: Option<PGNMoveSpecifier>,
-    dest: SQ,
-    promo: Option<PieceType>,
-    capture: bool,
+    pub piece: Option<PieceType>,
+    pub specifier: Option<PGNMoveSpecifier>,
+    pub dest: SQ,
+    pub promo: Option<PieceType>,
+    pub capture: bool,
 }
 
 //
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum PGNMoveType {
     KingSideCastle,  // O-O
     QueenSideCastle, // O-O-O
     Reg(PGNRegMove),
 }
 
-// (move)(check ?)(tag)
+/// A single move in Standard Algebraic Notation, as given in the movetext of a PGN.
+///
+/// (move)(check ?)(tag)
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct PGNMove {
-    move_type: PGNMoveType,
-    check: Option<CheckType>,
-    tag: PGNMoveTag,
+    pub move_type: PGNMoveType,
+    pub check: Option<CheckType>,
+    pub tag: PGNMoveTag,
 }
 
 impl PGNMove {
-    pub fn parse(_input: &str) -> Result<PGNMove, PGNError> {
-        unimplemented!()
+    /// Parses a SAN move, such as "Nbd7", "exd6", "e8=Q+" or "O-O-O". The move is only checked
+    /// for correct syntax, not legality.
+    pub fn parse(input: &str) -> Result<PGNMove, PGNError> {
+        let err = || PGNError::MoveParse {
+            token: input.to_owned(),
+        };
+
+        // Suffix annotations, e.g. "!?"
+        let mut mov: &str = input.trim_end_matches(|c| c == '!' || c == '?');
+        let tag = PGNMoveTag::parse(&input[mov.len()..]).ok_or_else(err)?;
+
+        let check = if mov.ends_with('#') {
+            mov = &mov[..mov.len() - 1];
+            Some(CheckType::CheckMate)
+        } else if mov.ends_with('+') {
+            mov = mov.trim_end_matches('+');
+            Some(CheckType::Check)
+        } else {
+            None
+        };
+
+        let move_type = match mov {
+            "O-O" | "0-0" => PGNMoveType::KingSideCastle,
+            "O-O-O" | "0-0-0" => PGNMoveType::QueenSideCastle,
+            _ => PGNMoveType::Reg(PGNRegMove::parse(mov).ok_or_else(err)?),
+        };
+
+        Ok(PGNMove {
+            move_type,
+            check,
+            tag,
+        })
+    }
+
+    /// Creates the SAN move describing a legal move of the board, including any
+    /// disambiguation and check or checkmate suffix that's needed.
+    pub fn from_bit_move(board: &Board, mov: BitMove) -> PGNMove {
+        let move_type = if mov.is_castle() {
+            if mov.is_king_castle() {
+                PGNMoveType::KingSideCastle
+            } else {
+                PGNMoveType::QueenSideCastle
+            }
+        } else {
+            let piece = board.moved_piece(mov).type_of();
+            let src = mov.get_src();
+            let capture = board.is_capture(mov);
+            let specifier = if piece == PieceType::P {
+                if capture {
+                    Some(PGNMoveSpecifier {
+                        rank: None,
+                        file: Some(src.file()),
+                        square: None,
+                    })
+                } else {
+                    None
+                }
+            } else {
+                disambiguate(board, mov, piece)
+            };
+            PGNMoveType::Reg(PGNRegMove {
+                piece: if piece == PieceType::P {
+                    None
+                } else {
+                    Some(piece)
+                },
+                specifier,
+                dest: mov.get_dest(),
+                promo: if mov.is_promo() {
+                    Some(mov.promo_piece())
+                } else {
+                    None
+                },
+                capture,
+            })
+        };
+
+        let check = if board.gives_check(mov) {
+            let mut after = board.shallow_clone();
+            after.apply_move(mov);
+            if after.generate_moves().is_empty() {
+                Some(CheckType::CheckMate)
+            } else {
+                Some(CheckType::Check)
+            }
+        } else {
+            None
+        };
+
+        PGNMove {
+            move_type,
+            check,
+            tag: PGNMoveTag::None,
+        }
+    }
+
+    /// Finds the legal move on the board that this move describes.
+    ///
+    /// Returns an error if no legal move matches, or if more than one does.
+    pub fn to_bit_move(&self, board: &Board) -> Result<BitMove, SanError> {
+        let mut found: Option<BitMove> = None;
+        for mov in board.generate_moves().iter() {
+            if self.matches(board, *mov) {
+                if found.is_some() {
+                    return Err(SanError::AmbiguousMove {
+                        san: self.to_string(),
+                    });
+                }
+                found = Some(*mov);
+            }
+        }
+        found.ok_or_else(|| SanError::IllegalMove {
+            san: self.to_string(),
+        })
+    }
+
+    // Returns if a legal move of the board is described by this move.
+    fn matches(&self, board: &Board, mov: BitMove) -> bool {
+        match self.move_type {
+            PGNMoveType::KingSideCastle => mov.is_castle() && mov.is_king_castle(),
+            PGNMoveType::QueenSideCastle => mov.is_castle() && mov.is_queen_castle(),
+            PGNMoveType::Reg(ref reg) => {
+                if mov.is_castle() || mov.get_dest() != reg.dest {
+                    return false;
+                }
+                let piece = reg.piece.unwrap_or(PieceType::P);
+                if board.moved_piece(mov).type_of() != piece {
+                    return false;
+                }
+                if let Some(ref spec) = reg.specifier {
+                    if !spec.matches(mov.get_src()) {
+                        return false;
+                    }
+                }
+                match reg.promo {
+                    Some(promo) => mov.is_promo() && mov.promo_piece() == promo,
+                    None => !mov.is_promo(),
+                }
+            }
+        }
     }
 }
 
-pub struct PGNRound {
-    move_num: u32,
-    white_move: PGNMove,
-    black_move: Option<PGNMove>,
+impl PGNRegMove {
+    // Parses a non-castling move, without any check or annotation suffixes.
+    fn parse(input: &str) -> Option<PGNRegMove> {
+        // Redundant en-passant suffix, e.g. "exd6e.p."
+        let mut mov: &str = input.trim_end_matches("e.p.").trim_end();
+        if !mov.is_ascii() || mov.len() < 2 {
+            return None;
+        }
+
+        let piece = match mov.as_bytes()[0] {
+            b'K' => Some(PieceType::K),
+            b'Q' => Some(PieceType::Q),
+            b'R' => Some(PieceType::R),
+            b'B' => Some(PieceType::B),
+            b'N' => Some(PieceType::N),
+            b'P' => None,
+            _ => None,
+        };
+        if piece.is_some() || mov.starts_with('P') {
+            mov = &mov[1..];
+        }
+
+        // Promotion, e.g. "e8=Q" or "e8Q"
+        let mut promo = None;
+        if let Some(last) = mov.chars().last() {
+            let promo_piece = match last {
+                'Q' | 'q' => Some(PieceType::Q),
+                'R' | 'r' => Some(PieceType::R),
+                'B' => Some(PieceType::B),
+                'N' | 'n' => Some(PieceType::N),
+                _ => None,
+            };
+            if promo_piece.is_some() && piece.is_none() {
+                promo = promo_piece;
+                mov = mov[..mov.len() - 1].trim_end_matches(|c| c == '=' || c == '/');
+            }
+        }
+
+        if mov.len() < 2 {
+            return None;
+        }
+        let dest = parse_square(&mov[mov.len() - 2..])?;
+        mov = &mov[..mov.len() - 2];
+
+        let capture = mov.ends_with('x') || mov.ends_with(':');
+        if capture {
+            mov = &mov[..mov.len() - 1];
+        }
+
+        let specifier = match mov.len() {
+            0 => None,
+            1 => {
+                let c = mov.as_bytes()[0];
+                Some(PGNMoveSpecifier {
+                    rank: parse_rank(c),
+                    file: parse_file(c),
+                    square: None,
+                })
+            }
+            2 => Some(PGNMoveSpecifier {
+                rank: None,
+                file: None,
+                square: Some(parse_square(mov)?),
+            }),
+            _ => return None,
+        };
+
+        if let Some(ref spec) = specifier {
+            if spec.rank.is_none() && spec.file.is_none() && spec.square.is_none() {
+                return None;
+            }
+        }
+
+        Some(PGNRegMove {
+            piece,
+            specifier,
+            dest,
+            promo,
+            capture,
+        })
+    }
 }
 
+impl fmt::Display for PGNMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::new();
+        match self.move_type {
+            PGNMoveType::KingSideCastle => s.push_str("O-O"),
+            PGNMoveType::QueenSideCastle => s.push_str("O-O-O"),
+            PGNMoveType::Reg(ref reg) => {
+                if let Some(piece) = reg.piece {
+                    s.push(piece.char_upper());
+                }
+                if let Some(ref spec) = reg.specifier {
+                    if let Some(sq) = spec.square {
+                        s.push_str(&sq.to_string());
+                    } else {
+                        if let Some(file) = spec.file {
+                            s.push((b'a' + file as u8) as char);
+                        }
+                        if let Some(rank) = spec.rank {
+                            s.push((b'1' + rank as u8) as char);
+                        }
+                    }
+                }
+                if reg.capture {
+                    s.push('x');
+                }
+                s.push_str(&reg.dest.to_string());
+                if let Some(promo) = reg.promo {
+                    s.push('=');
+                    s.push(promo.char_upper());
+                }
+            }
+        }
+        match self.check {
+            Some(CheckType::Check) => s.push('+'),
+            Some(CheckType::CheckMate) => s.push('#'),
+            None => {}
+        }
+        s.push_str(match self.tag {
+            PGNMoveTag::None => "",
+            PGNMoveTag::Good => "!",
+            PGNMoveTag::Excellent => "!!",
+            PGNMoveTag::Bad => "?",
+            PGNMoveTag::Blunder => "??",
+            PGNMoveTag::Interesting => "!?",
+            PGNMoveTag::Doubtful => "?!",
+        });
+        f.pad(&s)
+    }
+}
+
+// Returns the minimal specifier needed to distinguish a move from any other legal move
+// of the same piece type to the same square.
+fn disambiguate(board: &Board, mov: BitMove, piece: PieceType) -> Option<PGNMoveSpecifier> {
+    let src = mov.get_src();
+    let others: Vec<SQ> = board
+        .generate_moves()
+        .iter()
+        .filter(|m| {
+            !m.is_castle()
+                && m.get_dest() == mov.get_dest()
+                && m.get_src() != src
+                && board.moved_piece(**m).type_of() == piece
+        })
+        .map(|m| m.get_src())
+        .collect();
+
+    if others.is_empty() {
+        None
+    } else if others.iter().all(|sq| sq.file() != src.file()) {
+        Some(PGNMoveSpecifier {
+            rank: None,
+            file: Some(src.file()),
+            square: None,
+        })
+    } else if others.iter().all(|sq| sq.rank() != src.rank()) {
+        Some(PGNMoveSpecifier {
+            rank: Some(src.rank()),
+            file: None,
+            square: None,
+        })
+    } else {
+        Some(PGNMoveSpecifier {
+            rank: None,
+            file: None,
+            square: Some(src),
+        })
+    }
+}
+
+fn parse_file(c: u8) -> Option<File> {
+    match c {
+        b'a'..=b'h' => Some(ALL_FILES[(c - b'a') as usize]),
+        _ => None,
+    }
+}
+
+fn parse_rank(c: u8) -> Option<Rank> {
+    match c {
+        b'1'..=b'8' => Some(ALL_RANKS[(c - b'1') as usize]),
+        _ => None,
+    }
+}
+
+fn parse_square(s: &str) -> Option<SQ> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    Some(SQ::make(parse_file(bytes[0])?, parse_rank(bytes[1])?))
+}
+
+/// A move of the movetext, alongside it's annotations and any alternatives to it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PGNNode {
+    /// The move played.
+    pub mov: PGNMove,
+    /// Numeric Annotation Glyphs following the move, e.g. `$1`.
+    pub nags: Vec<u8>,
+    /// Comment following the move, if any.
+    pub comment: Option<String>,
+    /// Recursive Annotation Variations, each being an alternative to this move.
+    pub variations: Vec<PGNVariation>,
+}
+
+/// A line of moves, being either the mainline of a game or a variation.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct PGNVariation {
+    /// Comment preceding the first move of the line, if any.
+    pub comment: Option<String>,
+    /// The moves of the line.
+    pub moves: Vec<PGNNode>,
+}
+
+/// Represents possible Errors encountered while parsing or replaying a PGN.
 #[derive(Debug)]
 pub enum PGNError {
+    /// A tag pair is malformed.
     TagParse,
+    /// The input contains no game.
     Length,
+    /// A token of the movetext could not be parsed.
+    MoveParse { token: String },
+    /// A comment is missing it's closing brace.
+    UnterminatedComment,
+    /// A variation is missing it's opening or closing parenthesis, or a variation
+    /// has no move preceding it.
+    UnbalancedVariation,
+    /// A move is not legal in the position it was played in.
+    IllegalMove { ply: u16, mov: String },
+    /// A move matches more than one legal move in the position it was played in.
+    AmbiguousMove { ply: u16, mov: String },
+    /// The `FEN` tag does not contain a valid position.
+    FenBuild(FenBuildError),
+}
+
+impl From<FenBuildError> for PGNError {
+    fn from(err: FenBuildError) -> PGNError {
+        PGNError::FenBuild(err)
+    }
 }
 
+/// A single parsed PGN game.
+#[derive(Clone, PartialEq, Debug)]
 pub struct PGN {
-    tags: PGNTags,
-    moves: Vec<PGNRound>,
+    /// The tag pairs of the game.
+    pub tags: PGNTags,
+    /// The mainline of the game.
+    pub moves: PGNVariation,
+    /// The game termination marker.
+    pub result: GameResult,
 }
 
-// [Event "F/S Return Match"]
 impl PGN {
+    /// Parses a single game. Any input after the first game is ignored.
     pub fn parse(input: &str) -> Result<PGN, PGNError> {
+        Parser::new(input).parse_game()?.ok_or(PGNError::Length)
+    }
+
+    /// Parses every game contained in the input, such as the contents of a `.pgn` database.
+    pub fn parse_all(input: &str) -> Result<Vec<PGN>, PGNError> {
+        let mut parser = Parser::new(input);
+        let mut games = Vec::new();
+        while let Some(game) = parser.parse_game()? {
+            games.push(game);
+        }
+        Ok(games)
+    }
+
+    /// Returns the starting position of the game. This is the standard starting position, unless
+    /// the `FEN` tag specifies otherwise. The board is in Chess960 mode if the `Variant` tag is
+    /// "Chess960".
+    pub fn start_board(&self) -> Result<Board, PGNError> {
+        let fen = self.tags.fen().unwrap_or(super::fen::OPENING_POS_FEN);
+        match self.tags.get("Variant") {
+            Some(variant) if variant.eq_ignore_ascii_case("chess960") => {
+                Ok(Board::from_chess960_fen(fen)?)
+            }
+            _ => Ok(Board::from_fen(fen)?),
+        }
+    }
+
+    /// Replays the mainline of the game, returning the final position as well as each move played.
+    ///
+    /// Returns an error if any move of the mainline is illegal or ambiguous.
+    pub fn replay(&self) -> Result<(Board, Vec<BitMove>), PGNError> {
+        let mut board = self.start_board()?;
+        let mut moves = Vec::with_capacity(self.moves.moves.len());
+        for node in self.moves.moves.iter() {
+            let ply = board.moves_played();
+            let mov = node.mov.to_bit_move(&board).map_err(|err| match err {
+                SanError::AmbiguousMove { san } => PGNError::AmbiguousMove { ply, mov: san },
+                SanError::IllegalMove { san } | SanError::Unparseable { san } => {
+                    PGNError::IllegalMove { ply, mov: san }
+                }
+            })?;
+            board.apply_move(mov);
+            moves.push(mov);
+        }
+        Ok((board, moves))
+    }
+
+    /// Returns the position after the mainline of the game has been played.
+    pub fn board(&self) -> Result<Board, PGNError> {
+        self.replay().map(|(board, _)| board)
+    }
+
+    /// Creates a PGN from the moves played on a `Board`, using the given tags.
+    ///
+    /// The moves are found by walking back through the previous states of the board. If the
+    /// board didn't start from the standard starting position, the `SetUp` and `FEN` tags are
+    /// added, as well as the `Variant` tag for Chess960 games. The result is taken from
+    /// `Board::outcome()` if the game is over by a rule that ends it automatically, otherwise
+    /// the result of the tags is used, as draws by repetition or the fifty move rule only end
+    /// the game once claimed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pleco::Board;
+    /// use pleco::board::pgn::{PGN, PGNTags, GameResult};
+    ///
+    /// let mut board = Board::start_pos();
+    /// for mov in ["f2f3", "e7e5", "g2g4", "d8h4"].iter() {
+    ///     board.apply_uci_move(mov);
+    /// }
+    ///
+    /// let pgn = PGN::from_board(&board, PGNTags::default());
+    /// assert_eq!(pgn.result, GameResult::BlackWins);
+    /// assert!(pgn.to_string().ends_with("1. f3 e5 2. g4 Qh4# 0-1\n"));
+    /// ```
+    pub fn from_board(board: &Board, mut tags: PGNTags) -> PGN {
+        let mut history: Vec<BitMove> = Vec::new();
+        let mut state = &board.state;
+        while let Some(ref prev) = state.prev {
+            if state.prev_move.is_null() {
+                break;
+            }
+            history.push(state.prev_move);
+            state = prev;
+        }
+        history.reverse();
+
+        // Undo each move from a copy of the board to find the starting position. As a
+        // shallow clone may have been made since the moves were played, the depth is set
+        // to allow for undoing all of them.
+        let mut start = board.parallel_clone();
+        start.depth = history.len() as u16;
+        for _ in 0..history.len() {
+            start.undo_move();
+        }
+
+        if board.is_chess960() {
+            tags = tags.parse_tag("Variant", "Chess960".to_owned()).unwrap();
+        }
+        let start_fen = start.fen();
+        if start_fen != super::fen::OPENING_POS_FEN || board.is_chess960() {
+            tags = tags
+                .parse_tag("SetUp", "1".to_owned())
+                .and_then(|t| t.parse_tag("FEN", start_fen))
+                .unwrap();
+        }
+
+        let mut moves = PGNVariation::default();
+        for mov in history {
+            moves.moves.push(PGNNode {
+                mov: PGNMove::from_bit_move(&start, mov),
+                nags: Vec::new(),
+                comment: None,
+                variations: Vec::new(),
+            });
+            start.apply_move(mov);
+        }
+
+        if let Some(outcome) = board.outcome() {
+            if outcome.termination.is_automatic() {
+                tags.result = outcome.result();
+            }
+        }
+
+        PGN {
+            result: tags.result,
+            tags,
+            moves,
+        }
+    }
+}
+
+/// Displays the game in PGN export format. The tag pairs are followed by an empty line and
+/// the movetext, which is wrapped to lines of at most 80 characters.
+impl fmt::Display for PGN {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = self.tags.to_string();
+        s.push('\n');
+
+        let start_ply = self.start_board().map(|b| b.moves_played()).unwrap_or(0);
+        let mut tokens: Vec<String> = Vec::new();
+        write_variation(&mut tokens, &self.moves, start_ply);
+        tokens.push(self.result.as_str().to_owned());
+
+        let mut line_len = 0;
+        for token in tokens.iter() {
+            if line_len != 0 && line_len + 1 + token.len() > PGN_LINE_WIDTH {
+                s.push('\n');
+                line_len = 0;
+            } else if line_len != 0 {
+                s.push(' ');
+                line_len += 1;
+            }
+            s.push_str(token);
+            line_len += token.len();
+        }
+        s.push('\n');
+        f.pad(&s)
+    }
+}
+
+/// The maximum length of a line of exported movetext.
+const PGN_LINE_WIDTH: usize = 80;
+
+// Splits the movetext of a line of moves into tokens, none of which contain whitespace
+// outside of comments.
+fn write_variation(tokens: &mut Vec<String>, line: &PGNVariation, ply: u16) {
+    let mut need_number = true;
+    if let Some(ref comment) = line.comment {
+        write_comment(tokens, comment);
+    }
+    for (i, node) in line.moves.iter().enumerate() {
+        let ply = ply + i as u16;
+        if ply % 2 == 0 {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        } else if need_number {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(node.mov.to_string());
+        need_number = false;
+
+        for nag in node.nags.iter() {
+            tokens.push(format!("${}", nag));
+        }
+        if let Some(ref comment) = node.comment {
+            write_comment(tokens, comment);
+            need_number = true;
+        }
+        for variation in node.variations.iter() {
+            let open = tokens.len();
+            write_variation(tokens, variation, ply);
+            if tokens.len() == open {
+                tokens.push("()".to_owned());
+            } else {
+                tokens[open].insert(0, '(');
+                tokens.last_mut().unwrap().push(')');
+            }
+            need_number = true;
+        }
+    }
+}
+
+fn write_comment(tokens: &mut Vec<String>, comment: &str) {
+    let open = tokens.len();
+    tokens.extend(comment.split_whitespace().map(|w| w.replace('}', "")));
+    if tokens.len() == open {
+        tokens.push("{}".to_owned());
+    } else {
+        tokens[open].insert(0, '{');
+        tokens.last_mut().unwrap().push('}');
+    }
+}
+
+// Tokenizer & parser for PGN input. Works on a stream of characters, so multiple tags
+// on a single line, or movetext directly following the tags, is accepted.
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser {
+            input,
+            chars: input.char_indices().peekable(),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn pos(&mut self) -> usize {
+        let len = self.input.len();
+        self.chars.peek().map_or(len, |&(i, _)| i)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        self.chars.next().map(|(_, c)| c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    // Skips whitespace and lines starting with the '%' escape character.
+    fn skip_whitespace_escapes(&mut self) {
+        loop {
+            self.skip_whitespace();
+            let pos = self.pos();
+            let line_start = pos == 0 || self.input[..pos].ends_with('\n');
+            if line_start && self.peek() == Some('%') {
+                self.skip_line();
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn parse_game(&mut self) -> Result<Option<PGN>, PGNError> {
+        self.skip_whitespace_escapes();
+        if self.peek().is_none() {
+            return Ok(None);
+        }
+
         let mut tags = PGNTags::default();
+        while self.peek() == Some('[') {
+            let (tag, data) = self.parse_tag_pair()?;
+            tags = tags.parse_tag(tag.as_ref(), data)?;
+            self.skip_whitespace_escapes();
+        }
 
-        let mut lines = input.lines();
+        let mut result = None;
+        let moves = self.parse_variation(false, &mut result)?;
+        Ok(Some(PGN {
+            tags,
+            moves,
+            result: result.unwrap_or(GameResult::Other),
+        }))
+    }
+
+    // Parses a tag pair, starting at the opening bracket.
+    fn parse_tag_pair(&mut self) -> Result<(String, String), PGNError> {
+        if self.bump() != Some('[') {
+            return Err(PGNError::TagParse);
+        }
+        self.skip_whitespace();
+        let mut tag = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                tag.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        self.skip_whitespace();
+        if tag.is_empty() || self.bump() != Some('"') {
+            return Err(PGNError::TagParse);
+        }
+        let mut data = String::new();
         loop {
-            let mut line = lines.next().ok_or(PGNError::Length)?;
-            if line == "" {
+            match self.bump() {
+                Some('\\') => data.push(self.bump().ok_or(PGNError::TagParse)?),
+                Some('"') => break,
+                Some('\n') | None => return Err(PGNError::TagParse),
+                Some(c) => data.push(c),
+            }
+        }
+        self.skip_whitespace();
+        if self.bump() != Some(']') {
+            return Err(PGNError::TagParse);
+        }
+        Ok((tag, data))
+    }
+
+    // Parses a line of moves. Ends at the closing parenthesis for a variation, or at the game
+    // termination marker for the mainline.
+    fn parse_variation(
+        &mut self,
+        nested: bool,
+        result: &mut Option<GameResult>,
+    ) -> Result<PGNVariation, PGNError> {
+        let mut line = PGNVariation::default();
+        loop {
+            self.skip_whitespace_escapes();
+            let c = match self.peek() {
+                Some(c) => c,
+                None if nested => return Err(PGNError::UnbalancedVariation),
+                None => return Ok(line),
+            };
+            match c {
+                '{' => {
+                    self.bump();
+                    let start = self.pos();
+                    loop {
+                        match self.bump() {
+                            Some('}') => break,
+                            Some(_) => {}
+                            None => return Err(PGNError::UnterminatedComment),
+                        }
+                    }
+                    let end = self.pos() - 1;
+                    let text = self.input[start..end].trim().to_owned();
+                    add_comment(&mut line, text);
+                }
+                ';' => {
+                    self.bump();
+                    let start = self.pos();
+                    self.skip_line();
+                    let end = self.pos();
+                    let text = self.input[start..end].trim().to_owned();
+                    add_comment(&mut line, text);
+                }
+                '$' => {
+                    self.bump();
+                    let token = self.take_token();
+                    let nag = token.parse::<u8>().map_err(|_| PGNError::MoveParse {
+                        token: format!("${}", token),
+                    })?;
+                    match line.moves.last_mut() {
+                        Some(node) => node.nags.push(nag),
+                        None => {
+                            return Err(PGNError::MoveParse {
+                                token: format!("${}", token),
+                            })
+                        }
+                    }
+                }
+                '(' => {
+                    self.bump();
+                    let variation = self.parse_variation(true, result)?;
+                    match line.moves.last_mut() {
+                        Some(node) => node.variations.push(variation),
+                        None => return Err(PGNError::UnbalancedVariation),
+                    }
+                }
+                ')' => {
+                    self.bump();
+                    if nested {
+                        return Ok(line);
+                    }
+                    return Err(PGNError::UnbalancedVariation);
+                }
+                '[' if !nested => {
+                    // The start of the next game, with the termination marker missing.
+                    return Ok(line);
+                }
+                _ => {
+                    let token = self.take_token();
+                    if token.is_empty() {
+                        self.bump();
+                        return Err(PGNError::MoveParse {
+                            token: c.to_string(),
+                        });
+                    }
+                    if let Some(res) = GameResult::parse(&token) {
+                        if nested {
+                            // Some variations end with a result, ignore it.
+                            continue;
+                        }
+                        *result = Some(res);
+                        return Ok(line);
+                    }
+                    self.parse_move_token(&mut line, &token)?;
+                }
+            }
+        }
+    }
+
+    // Takes a token consisting of characters that aren't whitespace or movetext delimiters.
+    fn take_token(&mut self) -> String {
+        let mut token = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || "{}();[]$".contains(c) {
                 break;
             }
-            line = line.trim();
-            tags = tags.add(line)?;
+            token.push(c);
+            self.bump();
         }
-        Ok(PGN {
-            tags,
-            moves: Vec::new(),
-        })
+        token
+    }
+
+    // Parses a move token, which may be preceded by a move number indication.
+    fn parse_move_token(&mut self, line: &mut PGNVariation, token: &str) -> Result<(), PGNError> {
+        let mut mov: &str = token;
+
+        // Move number indication, e.g. "12." or "12..."
+        let digits = mov.len() - mov.trim_start_matches(|c: char| c.is_digit(10)).len();
+        if digits > 0 && mov[digits..].starts_with('.') {
+            mov = mov[digits..].trim_start_matches('.');
+        } else if mov.starts_with('.') {
+            mov = mov.trim_start_matches('.');
+        }
+        if mov.is_empty() || mov == "e.p." {
+            return Ok(());
+        }
+
+        // Separated suffix annotation, e.g. "e4 !?"
+        if let Some(tag) = PGNMoveTag::parse(mov) {
+            return match line.moves.last_mut() {
+                Some(node) => {
+                    node.mov.tag = tag;
+                    Ok(())
+                }
+                None => Err(PGNError::MoveParse {
+                    token: token.to_owned(),
+                }),
+            };
+        }
+
+        let mov = PGNMove::parse(mov)?;
+        line.moves.push(PGNNode {
+            mov,
+            nags: Vec::new(),
+            comment: None,
+            variations: Vec::new(),
+        });
+        Ok(())
+    }
+}
+
+// Attaches a comment to the last move of a line, or to the line itself if no move has been
+// played yet.
+fn add_comment(line: &mut PGNVariation, text: String) {
+    let comment = match line.moves.last_mut() {
+        Some(node) => &mut node.comment,
+        None => &mut line.comment,
+    };
+    match *comment {
+        Some(ref mut c) => {
+            c.push(' ');
+            c.push_str(&text);
+        }
+        None => *comment = Some(text),
     }
 }
 
@@ -334,21 +1319,217 @@ mod tests {
     static TEST_BLACK: &'static str = "[Black \"Grace Foo Bar\"]";
     static TEST_DATE: &'static str = "[Date \"2017.4.2\"]";
     static TEST_ROUND: &'static str = "[Round \"0.0\"]";
-    static TEST_RESULT: &'static str = "[Round \"1-0\"]";
+    static TEST_RESULT: &'static str = "[Result \"1-0\"]";
+
+    static FISCHER_SPASSKY: &'static str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+"#;
 
     extern crate rand;
     use super::*;
 
     #[test]
     fn tags_test() {
-        PGNTags::default()
-            .add(TEST_WHITE)
+        let tags = PGNTags::default()
+            .add_tag(TEST_WHITE)
+            .unwrap()
+            .add_tag(TEST_BLACK)
             .unwrap()
-            .add(TEST_BLACK)
+            .add_tag(TEST_DATE)
             .unwrap()
-            .add(TEST_DATE)
+            .add_tag(TEST_ROUND)
             .unwrap()
-            .add(TEST_ROUND)
+            .add_tag(TEST_RESULT)
+            .unwrap()
+            .add_tag("[ECO \"C95\"]")
             .unwrap();
+        assert_eq!(tags.white, "David Sr. Johnson");
+        assert_eq!(tags.date, ChessDate::Full(2017, 4, 2));
+        assert_eq!(tags.round.rounds(), &[0, 0]);
+        assert_eq!(tags.result, GameResult::WhiteWins);
+        assert_eq!(tags.get("ECO"), Some("C95"));
+        assert!(PGNTags::default().add_tag("[White David]").is_err());
+        assert!(PGNTags::default().add_tag("White \"David\"").is_err());
+    }
+
+    #[test]
+    fn san_parse() {
+        let m = PGNMove::parse("Nbd7").unwrap();
+        match m.move_type {
+            PGNMoveType::Reg(reg) => {
+                assert_eq!(reg.piece, Some(PieceType::N));
+                assert_eq!(reg.specifier.unwrap().file, Some(File::B));
+                assert_eq!(reg.dest, SQ::D7);
+            }
+            _ => panic!(),
+        }
+        let m = PGNMove::parse("exd8=Q#!?").unwrap();
+        assert_eq!(m.check, Some(CheckType::CheckMate));
+        assert_eq!(m.tag, PGNMoveTag::Interesting);
+        match m.move_type {
+            PGNMoveType::Reg(reg) => {
+                assert_eq!(reg.promo, Some(PieceType::Q));
+                assert!(reg.capture);
+            }
+            _ => panic!(),
+        }
+        assert_eq!(
+            PGNMove::parse("O-O-O+").unwrap().move_type,
+            PGNMoveType::QueenSideCastle
+        );
+        assert!(PGNMove::parse("Nz9").is_err());
+        assert!(PGNMove::parse("xx").is_err());
+    }
+
+    #[test]
+    fn full_game() {
+        let pgn = PGN::parse(FISCHER_SPASSKY).unwrap();
+        assert_eq!(pgn.tags.event, "F/S Return Match");
+        assert_eq!(pgn.tags.date, ChessDate::Full(1992, 11, 4));
+        assert_eq!(pgn.result, GameResult::Draw);
+        assert_eq!(pgn.moves.moves.len(), 85);
+        assert_eq!(
+            pgn.moves.moves[4].comment,
+            Some("This opening is called the Ruy Lopez.".to_owned())
+        );
+        let (board, moves) = pgn.replay().unwrap();
+        assert_eq!(moves.len(), 85);
+        assert_eq!(board.fen(), "8/8/4R1p1/2k3p1/1p4P1/1P1b1P2/3K1n2/8 b - - 2 43");
+    }
+
+    #[test]
+    fn variations_nags() {
+        let input = "1. e4 $1 (1. d4 d5 (1... Nf6 2. c4) 2. c4) ; mainline\n1... e5 *";
+        let pgn = PGN::parse(input).unwrap();
+        let first = &pgn.moves.moves[0];
+        assert_eq!(first.nags, vec![1]);
+        assert_eq!(first.comment, Some("mainline".to_owned()));
+        assert_eq!(first.variations.len(), 1);
+        assert_eq!(first.variations[0].moves.len(), 3);
+        assert_eq!(first.variations[0].moves[1].variations[0].moves.len(), 2);
+        assert_eq!(pgn.moves.moves.len(), 2);
+        assert_eq!(pgn.result, GameResult::Other);
+
+        assert!(PGN::parse("1. e4 (1. d4 *").is_err());
+        assert!(PGN::parse("1. e4 {unterminated *").is_err());
+    }
+
+    #[test]
+    fn multiple_games() {
+        let input = "[Result \"1-0\"]\n1. f3 e5 2. g4 Qh4# 0-1\n\n[Result \"0-1\"]\n1. e4 *\n";
+        let games = PGN::parse_all(input).unwrap();
+        assert_eq!(games.len(), 2);
+        let board = games[0].board().unwrap();
+        assert!(board.checkmate());
+    }
+
+    #[test]
+    fn replay_errors() {
+        match PGN::parse("1. e4 e5 2. Ke3 *").unwrap().replay() {
+            Err(PGNError::IllegalMove { ply, .. }) => assert_eq!(ply, 2),
+            _ => panic!(),
+        }
+        let fen_game = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1\"]\n1. R2f1 *";
+        match PGN::parse(fen_game).unwrap().replay() {
+            Err(PGNError::IllegalMove { .. }) => {}
+            _ => panic!(),
+        }
+        let fen_game = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4K3/R6R w - - 0 1\"]\n1. Rd1 *";
+        match PGN::parse(fen_game).unwrap().replay() {
+            Err(PGNError::AmbiguousMove { .. }) => {}
+            _ => panic!(),
+        }
+        let fen_game = "[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1\"]\n1. O-O-O Kf7 *";
+        assert!(PGN::parse(fen_game).unwrap().replay().is_ok());
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let pgn = PGN::parse(FISCHER_SPASSKY).unwrap();
+        let board = pgn.board().unwrap();
+        let mut exported = PGN::from_board(&board, pgn.tags.clone());
+        assert_eq!(exported.result, GameResult::Draw);
+        exported.moves.moves[4].comment = pgn.moves.moves[4].comment.clone();
+
+        let text = exported.to_string();
+        assert!(text.starts_with("[Event \"F/S Return Match\"]\n"));
+        let flat = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+        assert!(flat.contains("3. Bb5 {This opening is called the Ruy Lopez.} 3... a6"));
+        assert!(flat.contains("10. d4 Nbd7"));
+        assert!(flat.contains("24. Bxf7+ Rxf7"));
+        assert!(flat.ends_with("43. Re6 1/2-1/2"));
+        assert!(text.lines().all(|l| l.len() <= PGN_LINE_WIDTH));
+
+        let reparsed = PGN::parse(&text).unwrap();
+        assert_eq!(reparsed.tags, exported.tags);
+        assert_eq!(reparsed.board().unwrap().fen(), board.fen());
+    }
+
+    #[test]
+    fn export_claimable_draw() {
+        let mut board = Board::start_pos();
+        for _ in 0..2 {
+            for mov in ["g1f3", "g8f6", "f3g1", "f6g8"].iter() {
+                board.apply_uci_move(mov);
+            }
+        }
+        assert!(board.outcome().is_some());
+        // A repetition only ends the game once a player claims the draw.
+        let pgn = PGN::from_board(&board, PGNTags::default());
+        assert_eq!(pgn.result, GameResult::Other);
+    }
+
+    #[test]
+    fn chess960_game() {
+        let game = "[Variant \"Chess960\"]\n1. Nf3 Nf6 2. g3 g6 3. Bg2 Bg7 4. O-O O-O *";
+        let pgn = PGN::parse(game).unwrap();
+        let (board, moves) = pgn.replay().unwrap();
+        assert!(board.is_chess960());
+        assert_eq!(format!("{}{}", moves[7].get_src(), moves[7].get_dest()), "e8h8");
+
+        let exported = PGN::from_board(&board, PGNTags::default());
+        assert_eq!(exported.tags.get("Variant"), Some("Chess960"));
+        assert_eq!(exported.tags.fen(), Some(super::super::fen::OPENING_POS_FEN));
+        assert!(exported.to_string().contains("4. O-O O-O *"));
+    }
+
+    #[test]
+    fn export_disambiguation() {
+        let board = Board::from_fen("4k3/8/8/8/1N3N2/8/1N6/R3K2R w KQ - 0 1").unwrap();
+        let san = |uci: &str| {
+            let mov = *board
+                .generate_moves()
+                .iter()
+                .find(|m| m.stringify() == uci)
+                .unwrap();
+            PGNMove::from_bit_move(&board, mov).to_string()
+        };
+        assert_eq!(san("f4d3"), "Nfd3");
+        assert_eq!(san("b4d3"), "Nb4d3");
+        assert_eq!(san("b2d3"), "N2d3");
+        assert_eq!(san("b4a6"), "Na6");
+        assert_eq!(san("a1d1"), "Rd1");
+        assert_eq!(san("e1c1"), "O-O-O");
+
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R b K - 0 1").unwrap();
+        board.apply_uci_move("e8d7");
+        board.apply_uci_move("e1g1");
+        let pgn = PGN::from_board(&board, PGNTags::default());
+        assert_eq!(pgn.tags.fen(), Some("4k3/8/8/8/8/8/8/4K2R b K - 0 1"));
+        assert!(pgn.to_string().ends_with("1... Kd7 2. O-O *\n"));
     }
 }
@@ -16,6 +16,7 @@ use super::magic;
 use super::psqt;
 use super::zobrist;
 
+use core::piece_move::BitMove;
 use core::score::{Score, Value};
 use {BitBoard, File, Piece, PieceType, Player, Rank, SQ};
 
@@ -38,6 +39,7 @@ pub fn init_statics() {
         magic::init_magics();
         compiler_fence(Ordering::SeqCst);
         boards::init_boards();
+        zobrist::init_cuckoo();
         fence(Ordering::SeqCst);
         compiler_fence(Ordering::SeqCst);
     });
@@ -213,6 +215,12 @@ pub fn z_no_pawns() -> u64 {
     zobrist::z_no_pawns()
 }
 
+/// Returns the reversible move that changes a position's Zobrist Hash by `key`, if any.
+#[inline(always)]
+pub fn cuckoo_move(key: u64) -> Option<BitMove> {
+    zobrist::cuckoo_move(key)
+}
+
 // PSQT FUNCTIONS
 
 /// Returns the score for a player's piece being at a particular square.
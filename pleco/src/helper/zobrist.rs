@@ -1,6 +1,10 @@
 use core::masks::*;
+use core::piece_move::BitMove;
+use core::ALL_PLAYERS;
 use tools::prng::PRNG;
-use {BitBoard, Piece, SQ};
+use {BitBoard, Piece, PieceType, SQ};
+
+use super::prelude::{bishop_moves, king_moves, knight_moves, queen_moves, rook_moves};
 
 /// Seed for the Zobrist's pseudo-random number generator.
 const ZOBRIST_SEED: u64 = 23_081;
@@ -20,6 +24,16 @@ static mut ZOBRIST_SIDE: u64 = 0;
 /// Zobrist key for having no pawns;
 static mut ZOBRIST_NO_PAWNS: u64 = 0;
 
+/// Number of entries in the cuckoo tables. Must be a power of two.
+const CUCKOO_SIZE: usize = 8192;
+
+/// Cuckoo hash table of the zobrist key differences made by every reversible non-pawn move,
+/// used for detecting upcoming repetitions.
+static mut CUCKOO_KEYS: [u64; CUCKOO_SIZE] = [0; CUCKOO_SIZE];
+
+/// The move corresponding to each key of the cuckoo table.
+static mut CUCKOO_MOVES: [BitMove; CUCKOO_SIZE] = [BitMove::null(); CUCKOO_SIZE];
+
 /// initialize the zobrist hash
 #[cold]
 pub fn init_zobrist() {
@@ -27,7 +41,7 @@ pub fn init_zobrist() {
 
     unsafe {
         for i in 0..SQ_CNT {
-            for j in (Piece::WhitePawn as usize)..(Piece::BlackKing as usize) {
+            for j in (Piece::WhitePawn as usize)..=(Piece::BlackKing as usize) {
                 ZOBRIST_PIECE_SQUARE[i][j] = rng.rand();
                 ZOBRIST_PIECE_SQUARE[i][j] = rng.rand();
             }
@@ -56,6 +70,75 @@ pub fn init_zobrist() {
     }
 }
 
+/// Initializes the cuckoo tables. Must be called after the zobrist keys and the magic
+/// `BitBoard`s have been initialized.
+#[cold]
+pub fn init_cuckoo() {
+    let piece_types = [
+        PieceType::N,
+        PieceType::B,
+        PieceType::R,
+        PieceType::Q,
+        PieceType::K,
+    ];
+    unsafe {
+        for player in ALL_PLAYERS.iter() {
+            for piece_type in piece_types.iter() {
+                let piece = Piece::make_lossy(*player, *piece_type);
+                for s1 in 0..SQ_CNT as u8 {
+                    for s2 in (s1 + 1)..SQ_CNT as u8 {
+                        let (sq1, sq2) = (SQ(s1), SQ(s2));
+                        if (pseudo_attacks(*piece_type, sq1) & sq2.to_bb()).is_empty() {
+                            continue;
+                        }
+                        let mut mov = BitMove::make_quiet(sq1, sq2);
+                        let mut key = z_square(sq1, piece) ^ z_square(sq2, piece) ^ z_side();
+                        let mut i = cuckoo_h1(key);
+                        loop {
+                            let (old_key, old_mov) = (CUCKOO_KEYS[i], CUCKOO_MOVES[i]);
+                            CUCKOO_KEYS[i] = key;
+                            CUCKOO_MOVES[i] = mov;
+                            key = old_key;
+                            mov = old_mov;
+                            if mov.is_null() {
+                                break;
+                            }
+                            // Push the displaced entry to its alternative slot.
+                            i = if i == cuckoo_h1(key) {
+                                cuckoo_h2(key)
+                            } else {
+                                cuckoo_h1(key)
+                            };
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Squares attacked by a piece on an otherwise empty board.
+fn pseudo_attacks(piece_type: PieceType, sq: SQ) -> BitBoard {
+    match piece_type {
+        PieceType::N => knight_moves(sq),
+        PieceType::B => bishop_moves(BitBoard(0), sq),
+        PieceType::R => rook_moves(BitBoard(0), sq),
+        PieceType::Q => queen_moves(BitBoard(0), sq),
+        PieceType::K => king_moves(sq),
+        _ => BitBoard(0),
+    }
+}
+
+#[inline(always)]
+fn cuckoo_h1(key: u64) -> usize {
+    (key & (CUCKOO_SIZE as u64 - 1)) as usize
+}
+
+#[inline(always)]
+fn cuckoo_h2(key: u64) -> usize {
+    ((key >> 16) & (CUCKOO_SIZE as u64 - 1)) as usize
+}
+
 #[inline(always)]
 pub fn z_square(sq: SQ, piece: Piece) -> u64 {
     debug_assert!(sq.is_okay());
@@ -83,3 +166,18 @@ pub fn z_side() -> u64 {
 pub fn z_no_pawns() -> u64 {
     unsafe { ZOBRIST_NO_PAWNS }
 }
+
+/// Returns the reversible move changing a position's zobrist key by `key`, if there is one.
+#[inline]
+pub fn cuckoo_move(key: u64) -> Option<BitMove> {
+    unsafe {
+        let mut i = cuckoo_h1(key);
+        if *CUCKOO_KEYS.get_unchecked(i) != key {
+            i = cuckoo_h2(key);
+            if *CUCKOO_KEYS.get_unchecked(i) != key {
+                return None;
+            }
+        }
+        Some(*CUCKOO_MOVES.get_unchecked(i))
+    }
+}
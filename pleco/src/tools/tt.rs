@@ -314,8 +314,7 @@ impl TranspositionTable {
         self.size_megabytes()
     }
 
-    // resizes the tt to a certain type
-    // TODO: Modify self.cap
+    // resizes the tt to a certain number of clusters.
     unsafe fn resize(&self, size: usize) {
         assert_eq!(size.count_ones(), 1);
         assert!(size > 0);
@@ -418,6 +417,7 @@ impl TranspositionTable {
     unsafe fn re_alloc(&self, size: usize) {
         let c = self.clusters.get();
         *c = alloc_room(size);
+        *self.cap.get() = size;
     }
 
     /// De-allocates the current heap.
@@ -530,6 +530,8 @@ mod tests {
             tt.num_entries(),
             (100 as usize).next_power_of_two() * CLUSTER_SIZE
         );
+        unsafe { tt.resize_round_up(1000) };
+        assert_eq!(tt.num_clusters(), (1000 as usize).next_power_of_two());
         compiler_fence(Ordering::Release);
         sleep(Duration::from_millis(1));
     }
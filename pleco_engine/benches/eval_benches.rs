@@ -3,6 +3,7 @@ use std::time::Duration;
 use criterion::{Criterion,black_box,Bencher,Fun, BatchSize};
 
 use pleco::{Board,Player};
+use pleco::core::score::Score;
 use pleco_engine::tables::pawn_table::{PawnEntry, PawnTable};
 use pleco_engine::tables::material::{Material, MaterialEntry};
 use pleco::core::mono_traits::WhiteType;
@@ -63,7 +64,7 @@ fn bench_100_eval(b: &mut Bencher,  boards: &Vec<Board>) {
         #[allow(unused_variables)]
         let mut score: i64 = 0;
         for board in boards.iter() {
-            score += black_box(Evaluation::evaluate(&board, &mut tp, &mut tm)) as i64;
+            score += black_box(Evaluation::evaluate(&board, &mut tp, &mut tm, Score::ZERO)) as i64;
         }
     }, BatchSize::PerIteration)
 }
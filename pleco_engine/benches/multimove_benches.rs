@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::time::Duration;
 use criterion::{Criterion,black_box,Bencher,BatchSize};
 
@@ -5,7 +6,6 @@ use pleco::{Board};
 
 use pleco_engine::engine::PlecoSearcher;
 use pleco_engine::time::uci_timer::PreLimits;
-use pleco_engine::threadpool::*;
 
 use super::*;
 
@@ -14,37 +14,37 @@ const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R
 fn search_kiwipete_3moves_engine<D: DepthLimit>(b: &mut Bencher) {
     let mut pre_limit = PreLimits::blank();
     pre_limit.depth = Some(D::depth());
-    let mut searcher = PlecoSearcher::init(false);
+    let searcher = RefCell::new(PlecoSearcher::init(false));
     let limit = pre_limit.create();
     let board_kwi: Board = Board::from_fen(KIWIPETE).unwrap();
     b.iter_batched(|| {
-        threadpool().clear_all();
-        searcher.clear_tt();
+        searcher.borrow_mut().threadpool().clear_all();
+        searcher.borrow_mut().clear_tt();
         board_kwi.shallow_clone()
     }, |mut board| {
-        let mov = black_box(threadpool().search(&board, &limit));
+        let mov = black_box(searcher.borrow_mut().threadpool().search(&board, &limit));
         board.apply_move(mov);
-        let mov = black_box(threadpool().search(&board, &limit));
+        let mov = black_box(searcher.borrow_mut().threadpool().search(&board, &limit));
         board.apply_move(mov);
-        black_box(threadpool().search(&board, &limit));
+        black_box(searcher.borrow_mut().threadpool().search(&board, &limit));
     }, BatchSize::PerIteration)
 }
 
 fn search_startpos_3moves_engine<D: DepthLimit>(b: &mut Bencher) {
     let mut pre_limit = PreLimits::blank();
     pre_limit.depth = Some(D::depth());
-    let mut searcher = PlecoSearcher::init(false);
+    let searcher = RefCell::new(PlecoSearcher::init(false));
     let limit = pre_limit.create();
     b.iter_batched(|| {
-        threadpool().clear_all();
-        searcher.clear_tt();
+        searcher.borrow_mut().threadpool().clear_all();
+        searcher.borrow_mut().clear_tt();
         Board::start_pos()
     }, |mut board| {
-        let mov = black_box(threadpool().search(&board, &limit));
+        let mov = black_box(searcher.borrow_mut().threadpool().search(&board, &limit));
         board.apply_move(mov);
-        let mov = black_box(threadpool().search(&board, &limit));
+        let mov = black_box(searcher.borrow_mut().threadpool().search(&board, &limit));
         board.apply_move(mov);
-        black_box(threadpool().search(&board, &limit));
+        black_box(searcher.borrow_mut().threadpool().search(&board, &limit));
     }, BatchSize::PerIteration)
 }
 
@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::time::Duration;
 use criterion::{Criterion,black_box,Bencher,BatchSize};
 
@@ -5,7 +6,6 @@ use pleco::{Board};
 
 use pleco_engine::engine::PlecoSearcher;
 use pleco_engine::time::uci_timer::PreLimits;
-use pleco_engine::threadpool::*;
 
 use super::*;
 
@@ -13,14 +13,14 @@ use super::*;
 fn search_singular_engine<D: DepthLimit>(b: &mut Bencher) {
     let mut pre_limit = PreLimits::blank();
     pre_limit.depth = Some(D::depth());
-    let mut searcher = PlecoSearcher::init(false);
+    let searcher = RefCell::new(PlecoSearcher::init(false));
     let limit = pre_limit.create();
     b.iter_batched(|| {
-        threadpool().clear_all();
-        searcher.clear_tt();
+        searcher.borrow_mut().threadpool().clear_all();
+        searcher.borrow_mut().clear_tt();
         Board::start_pos()
     }, |board| {
-        black_box(threadpool().search(&board, &limit));
+        black_box(searcher.borrow_mut().threadpool().search(&board, &limit));
     }, BatchSize::PerIteration)
 }
 
@@ -0,0 +1,319 @@
+//! Fits the win rate model used by `UCI_ShowWDL` to the results of self-play games.
+//!
+//! ```bash
+//! cargo run --release --example fit_wdl -- games.pgn [more_games.pgn ...]
+//! ```
+//!
+//! Every move of the games is expected to have a comment starting with the score reported by
+//! the engine that played it, in pawns and from the perspective of that engine, as written by
+//! the `selfplay` example or `cutechess-cli`: `{+0.35/12 0.104s}`. Moves without a score, such as book moves, and mate
+//! scores are skipped.
+//!
+//! For every bucket of game plies, the model parameters `a` and `b` are found by maximizing the
+//! likelihood of the game results. Cubic polynomials of `ply / 64` are then fitted through the
+//! parameters of each bucket, and printed out to replace the ones in `pleco_engine::uci::wdl`,
+//! along with the range of plies covered by the buckets used.
+
+extern crate pleco;
+extern crate pleco_engine;
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::process;
+
+use pleco::board::pgn::{GameResult, PGN};
+use pleco::Player;
+
+use pleco_engine::uci::wdl::win_probability;
+
+/// Number of game plies grouped together when fitting the parameters.
+const PLY_BUCKET: u16 = 8;
+
+/// Minimum number of games a bucket needs positions from to be used in the fit. The positions
+/// of a single game all share its result, so the number of positions alone says little.
+const MIN_BUCKET_GAMES: usize = 100;
+
+/// Positions with a score further from zero than this are not used, as nearly all of them are
+/// decided and tell little about the shape of the model.
+const MAX_FIT_CP: f64 = 1000.0;
+
+/// Upper bound of the parameters, in centipawns. A bucket whose fit reaches it has results that
+/// barely depend on the score, and is left out.
+const MAX_PARAMETER: f64 = 1000.0;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// A scored position, from the perspective of the side to move.
+struct Sample {
+    game: usize,
+    ply: u16,
+    cp: f64,
+    result: Outcome,
+}
+
+fn main() {
+    let paths: Vec<String> = env::args().skip(1).collect();
+    if paths.is_empty() {
+        eprintln!("usage: fit_wdl <games.pgn> [more_games.pgn ...]");
+        process::exit(1);
+    }
+
+    let mut samples = Vec::new();
+    let mut game_count = 0;
+    for path in paths.iter() {
+        let mut contents = String::new();
+        if let Err(e) = File::open(path).and_then(|mut f| f.read_to_string(&mut contents)) {
+            eprintln!("unable to read '{}': {}", path, e);
+            process::exit(1);
+        }
+        match PGN::parse_all(&contents) {
+            Ok(games) => {
+                for game in games.iter() {
+                    read_game(game, game_count, &mut samples);
+                    game_count += 1;
+                }
+            }
+            Err(e) => {
+                eprintln!("unable to parse '{}': {:?}", path, e);
+                process::exit(1);
+            }
+        }
+    }
+    println!(
+        "{} scored positions from {} games",
+        samples.len(),
+        game_count
+    );
+
+    // (m, a, b, positions) of each bucket, and the range of plies they cover
+    let mut fits: Vec<(f64, f64, f64, f64)> = Vec::new();
+    let mut covered: Option<(u16, u16)> = None;
+    let last_ply = samples.iter().map(|s| s.ply).max().unwrap_or(0);
+    let mut start: u16 = 0;
+    while start <= last_ply {
+        let end = start + PLY_BUCKET;
+        let bucket: Vec<&Sample> = samples
+            .iter()
+            .filter(|s| s.ply >= start && s.ply < end)
+            .collect();
+        let mut games: Vec<usize> = bucket.iter().map(|s| s.game).collect();
+        games.dedup();
+        if games.len() >= MIN_BUCKET_GAMES {
+            let (a, b) = fit_bucket(&bucket);
+            let m = (start + end - 1) as f64 / 2.0 / 64.0;
+            println!(
+                "plies {:3}-{:3}: a = {:7.2}, b = {:7.2} ({} positions)",
+                start,
+                end - 1,
+                a,
+                b,
+                bucket.len()
+            );
+            if a.max(b) < MAX_PARAMETER * 0.99 {
+                fits.push((m, a, b, bucket.len() as f64));
+                covered = Some((covered.map_or(start, |c| c.0), end - 1));
+            }
+        }
+        start = end;
+    }
+
+    if fits.len() < 4 {
+        eprintln!("not enough positions to fit the model");
+        process::exit(1);
+    }
+
+    let a_points: Vec<(f64, f64, f64)> = fits.iter().map(|f| (f.0, f.1, f.3)).collect();
+    let b_points: Vec<(f64, f64, f64)> = fits.iter().map(|f| (f.0, f.2, f.3)).collect();
+    println!();
+    println!(
+        "pub const WIN_RATE_A: [f64; 4] = {};",
+        format_coeffs(&fit_cubic(&a_points))
+    );
+    println!(
+        "pub const WIN_RATE_B: [f64; 4] = {};",
+        format_coeffs(&fit_cubic(&b_points))
+    );
+    let (min_ply, max_ply) = covered.unwrap();
+    println!("pub const MIN_MODEL_PLY: u16 = {};", min_ply);
+    println!("pub const MAX_MODEL_PLY: u16 = {};", max_ply);
+}
+
+// Collects the scored positions of a game with a known result.
+fn read_game(game: &PGN, index: usize, samples: &mut Vec<Sample>) {
+    let winner = match game.result {
+        GameResult::WhiteWins => Some(Player::White),
+        GameResult::BlackWins => Some(Player::Black),
+        GameResult::Draw => None,
+        GameResult::Other => return,
+    };
+    let board = match game.start_board() {
+        Ok(board) => board,
+        Err(_) => return,
+    };
+
+    let mut turn = board.turn();
+    for (ply, node) in (board.moves_played()..).zip(game.moves.moves.iter()) {
+        if let Some(cp) = node.comment.as_ref().and_then(|c| parse_score(c)) {
+            if cp.abs() <= MAX_FIT_CP {
+                let result = match winner {
+                    None => Outcome::Draw,
+                    Some(player) if player == turn => Outcome::Win,
+                    Some(_) => Outcome::Loss,
+                };
+                samples.push(Sample {
+                    game: index,
+                    ply,
+                    cp,
+                    result,
+                });
+            }
+        }
+        turn = !turn;
+    }
+}
+
+// Parses the score in centipawns at the start of a comment such as "+0.35/12 0.104s".
+fn parse_score(comment: &str) -> Option<f64> {
+    let score = comment.split_whitespace().next()?.split('/').next()?;
+    if score.contains('M') {
+        return None;
+    }
+    score.parse::<f64>().ok().map(|pawns| pawns * 100.0)
+}
+
+// The negative log likelihood of the results of the positions with the given parameters.
+fn neg_log_likelihood(bucket: &[&Sample], a: f64, b: f64) -> f64 {
+    if a <= 0.0 || b <= 0.0 || a > MAX_PARAMETER || b > MAX_PARAMETER {
+        return std::f64::INFINITY;
+    }
+    bucket
+        .iter()
+        .map(|s| {
+            let win = win_probability(s.cp, a, b);
+            let loss = win_probability(-s.cp, a, b);
+            let p = match s.result {
+                Outcome::Win => win,
+                Outcome::Draw => 1.0 - win - loss,
+                Outcome::Loss => loss,
+            };
+            -p.max(1e-12).ln()
+        })
+        .sum()
+}
+
+// Finds the parameters of a bucket with the Nelder-Mead method.
+fn fit_bucket(bucket: &[&Sample]) -> (f64, f64) {
+    let f = |p: [f64; 2]| neg_log_likelihood(bucket, p[0], p[1]);
+    let mut simplex = [[100.0, 50.0], [150.0, 50.0], [100.0, 100.0]];
+    let mut values = [f(simplex[0]), f(simplex[1]), f(simplex[2])];
+
+    for _ in 0..500 {
+        // Order the vertices from best to worst.
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| values[i].partial_cmp(&values[j]).unwrap());
+        simplex = [simplex[order[0]], simplex[order[1]], simplex[order[2]]];
+        values = [values[order[0]], values[order[1]], values[order[2]]];
+
+        let size = (simplex[2][0] - simplex[0][0]).abs() + (simplex[2][1] - simplex[0][1]).abs();
+        if size < 1e-4 {
+            break;
+        }
+
+        let centroid = [
+            (simplex[0][0] + simplex[1][0]) / 2.0,
+            (simplex[0][1] + simplex[1][1]) / 2.0,
+        ];
+        let along = |t: f64| {
+            [
+                centroid[0] + t * (simplex[2][0] - centroid[0]),
+                centroid[1] + t * (simplex[2][1] - centroid[1]),
+            ]
+        };
+
+        let reflected = along(-1.0);
+        let reflected_value = f(reflected);
+        if reflected_value < values[0] {
+            let expanded = along(-2.0);
+            let expanded_value = f(expanded);
+            if expanded_value < reflected_value {
+                simplex[2] = expanded;
+                values[2] = expanded_value;
+            } else {
+                simplex[2] = reflected;
+                values[2] = reflected_value;
+            }
+        } else if reflected_value < values[1] {
+            simplex[2] = reflected;
+            values[2] = reflected_value;
+        } else {
+            let contracted = along(0.5);
+            let contracted_value = f(contracted);
+            if contracted_value < values[2] {
+                simplex[2] = contracted;
+                values[2] = contracted_value;
+            } else {
+                // Shrink towards the best vertex.
+                for i in 1..3 {
+                    simplex[i] = [
+                        (simplex[0][0] + simplex[i][0]) / 2.0,
+                        (simplex[0][1] + simplex[i][1]) / 2.0,
+                    ];
+                    values[i] = f(simplex[i]);
+                }
+            }
+        }
+    }
+    (simplex[0][0], simplex[0][1])
+}
+
+// Fits a cubic polynomial through weighted (x, y, weight) points with least squares, returning
+// the coefficients from the highest order to the constant term.
+fn fit_cubic(points: &[(f64, f64, f64)]) -> [f64; 4] {
+    // Normal equations of the fit, as an augmented matrix.
+    let mut m = [[0.0; 5]; 4];
+    for &(x, y, w) in points.iter() {
+        let powers = [x * x * x, x * x, x, 1.0];
+        for i in 0..4 {
+            for j in 0..4 {
+                m[i][j] += w * powers[i] * powers[j];
+            }
+            m[i][4] += w * powers[i] * y;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for col in 0..4 {
+        let pivot = (col..4)
+            .max_by(|&i, &j| m[i][col].abs().partial_cmp(&m[j][col].abs()).unwrap())
+            .unwrap();
+        m.swap(col, pivot);
+        for row in 0..4 {
+            if row != col {
+                let factor = m[row][col] / m[col][col];
+                let pivot_row = m[col];
+                for (k, value) in m[row].iter_mut().enumerate().skip(col) {
+                    *value -= factor * pivot_row[k];
+                }
+            }
+        }
+    }
+    [
+        m[0][4] / m[0][0],
+        m[1][4] / m[1][1],
+        m[2][4] / m[2][2],
+        m[3][4] / m[3][3],
+    ]
+}
+
+fn format_coeffs(coeffs: &[f64; 4]) -> String {
+    format!(
+        "[{:.2}, {:.2}, {:.2}, {:.2}]",
+        coeffs[0], coeffs[1], coeffs[2], coeffs[3]
+    )
+}
@@ -0,0 +1,149 @@
+//! Plays the engine against itself, writing the games as PGN to stdout for `fit_wdl`.
+//!
+//! ```bash
+//! cargo run --release --example selfplay -- <games> <nodes> <seed> > games.pgn
+//! ```
+//!
+//! Each game starts from a few random moves out of the starting position, chosen by `seed`.
+//! Openings the engine scores at more than `MAX_OPENING_CP` either way are thrown out, so the
+//! games start out roughly balanced. The game is then played with a single thread searching
+//! `nodes` nodes per move. Every move is followed by the score and depth of the search that
+//! played it, such as `{+0.35/12}`.
+//!
+//! A game is adjudicated once both sides have agreed on the same side leading by at least
+//! `RESIGN_CP` for `RESIGN_PLIES` plies in a row. Draws by repetition or the fifty move rule are claimed right
+//! away, and games that go on for `MAX_PLIES` plies are drawn.
+
+extern crate pleco;
+extern crate pleco_engine;
+
+use std::env;
+use std::process;
+
+use pleco::board::pgn::{GameResult, PGNTags, PGN};
+use pleco::{Board, Player};
+
+use pleco_engine::engine::PlecoSearcher;
+use pleco_engine::search::info::SearchScore;
+use pleco_engine::time::uci_timer::PreLimits;
+
+/// Number of random moves played at the start of each game.
+const OPENING_PLIES: usize = 6;
+
+/// The largest score in centipawns an opening can have.
+const MAX_OPENING_CP: i32 = 100;
+
+/// Score in centipawns at which the losing side resigns.
+const RESIGN_CP: i32 = 1000;
+
+/// Number of plies in a row the score must stay decisive for before resigning.
+const RESIGN_PLIES: usize = 8;
+
+/// Games are drawn after this many plies.
+const MAX_PLIES: u16 = 400;
+
+fn main() {
+    let args: Vec<u64> = env::args()
+        .skip(1)
+        .map(|arg| arg.parse().unwrap_or(0))
+        .collect();
+    if args.len() != 3 || args[0] == 0 || args[1] == 0 {
+        eprintln!("usage: selfplay <games> <nodes> <seed>");
+        process::exit(1);
+    }
+    let (games, nodes, mut seed) = (args[0], args[1], args[2] | 1);
+
+    let mut searcher = PlecoSearcher::init(false);
+    searcher.threadpool().set_thread_count(1);
+    let mut limits = PreLimits::blank();
+    limits.nodes = Some(nodes);
+
+    for round in 1..=games {
+        searcher.clear_search();
+        let mut board = random_opening(&mut seed);
+        while !balanced(&mut searcher, &board, &limits) {
+            board = random_opening(&mut seed);
+        }
+        let mut comments: Vec<Option<String>> = vec![None; OPENING_PLIES];
+
+        let mut result = GameResult::Draw;
+        let mut decisive_plies = 0;
+        let mut previous_leader = Player::White;
+        while board.outcome().is_none() && board.moves_played() < MAX_PLIES {
+            searcher.search(&board, &limits).unwrap();
+            let search = searcher.await_result().unwrap();
+            let (comment, cp) = match search.score {
+                SearchScore::Cp(cp) => (format!("{:+.2}/{}", cp as f64 / 100.0, search.depth), cp),
+                SearchScore::Mate(moves) if moves > 0 => {
+                    (format!("+M{}/{}", moves, search.depth), RESIGN_CP)
+                }
+                SearchScore::Mate(moves) => (format!("-M{}/{}", -moves, search.depth), -RESIGN_CP),
+            };
+            comments.push(Some(comment));
+            board.apply_move(search.best_move);
+
+            // The score is from the side that just moved.
+            let leader = if cp > 0 { !board.turn() } else { board.turn() };
+            if cp.abs() < RESIGN_CP {
+                decisive_plies = 0;
+            } else if decisive_plies > 0 && leader != previous_leader {
+                decisive_plies = 1;
+            } else {
+                decisive_plies += 1;
+            }
+            previous_leader = leader;
+            if decisive_plies >= RESIGN_PLIES {
+                result = match leader {
+                    Player::White => GameResult::WhiteWins,
+                    Player::Black => GameResult::BlackWins,
+                };
+                break;
+            }
+        }
+
+        let mut tags = PGNTags::default()
+            .parse_tag("Event", "selfplay".to_owned())
+            .and_then(|t| t.parse_tag("Round", round.to_string()))
+            .unwrap();
+        tags.result = result;
+        let mut pgn = PGN::from_board(&board, tags);
+        for (node, comment) in pgn.moves.moves.iter_mut().zip(comments) {
+            node.comment = comment;
+        }
+        println!("{}", pgn);
+    }
+}
+
+// Plays random moves from the starting position, until reaching a position that isn't over.
+fn random_opening(seed: &mut u64) -> Board {
+    loop {
+        let mut board = Board::start_pos();
+        for _ in 0..OPENING_PLIES {
+            let moves = board.generate_moves();
+            if moves.is_empty() {
+                break;
+            }
+            board.apply_move(moves[(xorshift(seed) % moves.len() as u64) as usize]);
+        }
+        if board.outcome().is_none() {
+            return board;
+        }
+    }
+}
+
+// Returns if neither side is clearly better in the position.
+fn balanced(searcher: &mut PlecoSearcher, board: &Board, limits: &PreLimits) -> bool {
+    searcher.search(board, limits).unwrap();
+    match searcher.await_result().unwrap().score {
+        SearchScore::Cp(cp) => cp.abs() <= MAX_OPENING_CP,
+        SearchScore::Mate(_) => false,
+    }
+}
+
+// A xorshift generator, so the openings only depend on the seed.
+fn xorshift(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
@@ -1,18 +1,12 @@
 //! Constant values and static structures.
-use std::mem;
-use std::ptr;
 use std::sync::atomic::compiler_fence;
-use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::{Once, ONCE_INIT};
 
 use pleco::helper::prelude;
-use pleco::tools::tt::TranspositionTable;
 
 use search;
 use tables::pawn_table;
-use threadpool;
-use time::time_management::TimeManager;
 
 pub const MAX_PLY: u16 = 126;
 pub const THREAD_STACK_SIZE: usize = MAX_PLY as usize + 7;
@@ -23,65 +17,28 @@ pub const DEFAULT_TT_SIZE: usize = 256;
 pub const PAWN_TABLE_SIZE: usize = 16384;
 pub const MATERIAL_TABLE_SIZE: usize = 8192;
 
-const TT_ALLOC_SIZE: usize = mem::size_of::<TranspositionTable>();
-const TIMER_ALLOC_SIZE: usize = mem::size_of::<TimeManager>();
+pub const MAX_SKILL_LEVEL: u8 = 20;
+pub const MIN_ELO: i32 = 1350;
+pub const MAX_ELO: i32 = 2850;
 
-// A object that is the same size as a transposition table
-type DummyTranspositionTable = [u8; TT_ALLOC_SIZE];
-type DummyTimeManager = [u8; TIMER_ALLOC_SIZE];
+pub const MAX_CONTEMPT: i32 = 100;
 
-pub static USE_STDOUT: AtomicBool = AtomicBool::new(true);
+pub const DEFAULT_BOOK_DEPTH: u16 = 24;
+pub const NO_BOOK_FILE: &str = "<empty>";
 
 static INITALIZED: Once = ONCE_INIT;
 
-/// Global Transposition Table
-static mut TT_TABLE: DummyTranspositionTable = [0; TT_ALLOC_SIZE];
-
-// Global Timer
-static mut TIMER: DummyTimeManager = [0; TIMER_ALLOC_SIZE];
-
+/// Initializes the lookup tables shared by every searcher.
 #[cold]
 pub fn init_globals() {
     INITALIZED.call_once(|| {
         prelude::init_statics(); // Initialize static tables
         compiler_fence(Ordering::SeqCst);
-        init_tt(); // Transposition Table
-        init_timer(); // Global timer manager
         pawn_table::init();
-        threadpool::init_threadpool(); // Make Threadpool
         search::init();
     });
 }
 
-// Initializes the transposition table
-#[cold]
-fn init_tt() {
-    unsafe {
-        let tt: *mut TranspositionTable = mem::transmute(&mut TT_TABLE);
-        ptr::write(tt, TranspositionTable::new(DEFAULT_TT_SIZE));
-    }
-}
-
-// Initializes the global Timer
-#[cold]
-fn init_timer() {
-    unsafe {
-        let timer: *mut TimeManager = mem::transmute(&mut TIMER);
-        ptr::write(timer, TimeManager::uninitialized());
-    }
-}
-
-// Returns access to the global timer
-pub fn timer() -> &'static TimeManager {
-    unsafe { mem::transmute(&TIMER) }
-}
-
-/// Returns access to the global transposition table
-#[inline(always)]
-pub fn tt() -> &'static TranspositionTable {
-    unsafe { mem::transmute(&TT_TABLE) }
-}
-
 pub trait PVNode {
     fn is_pv() -> bool;
 }
@@ -104,8 +61,12 @@ impl PVNode for NonPV {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use threadpool::ThreadPool;
+
     #[test]
     fn initializing_threadpool() {
-        threadpool::init_threadpool();
+        init_globals();
+        let pool = ThreadPool::new(1);
+        assert_eq!(pool.size(), 1);
     }
 }
@@ -1,18 +1,25 @@
 //! The main searching structure.
 
-use std::io;
+use std::io::{self, BufRead, Write};
 use std::sync::atomic::Ordering;
 
+use pleco::board::polyglot::PolyglotBook;
 use pleco::BitMove;
 use pleco::Board;
+use pleco::Player;
 
 use consts::*;
-use threadpool::threadpool;
-use time::uci_timer::PreLimits;
-use uci::options::{OptionWork, OptionsMap};
-use uci::parse;
+use threadpool::ThreadPool;
+use time::time_management::{DEFAULT_MOVE_OVERHEAD, DEFAULT_SLOW_MOVER};
+use time::uci_timer::{Limits, PreLimits};
+use uci::options::{AnalysisContempt, OptionWork, OptionsMap};
+use uci::output::Output;
+use uci::parse::{self, SearchMovesError};
+use xboard;
 
 use search::eval::Evaluation;
+use search::info::{InfoSink, SearchResult};
+use search::skill::{self, SKILL_MULTI_PV};
 
 use num_cpus;
 
@@ -30,75 +37,230 @@ enum SearchType {
 }
 
 pub struct PlecoSearcher {
+    pool: Box<ThreadPool>,
     options: OptionsMap,
     search_mode: SearchType,
     board: Board,
     multipv: usize,
+    book: Option<PolyglotBook>,
+    own_book: bool,
+    book_depth: u16,
+    book_variety: bool,
+    skill_level: u8,
+    limit_strength: bool,
+    elo: u16,
+    contempt: i32,
+    analysis_contempt: AnalysisContempt,
+    show_wdl: bool,
+    ponder: bool,
+    move_overhead: i64,
+    slow_mover: i64,
+    nodes_time: i64,
 }
 
 impl PlecoSearcher {
     pub fn init(use_stdout: bool) -> Self {
         init_globals();
-        USE_STDOUT.store(use_stdout, Ordering::Relaxed);
-        threadpool().set_thread_count(num_cpus::get().min(MAX_THREADS).max(1));
+        let mut pool = ThreadPool::new(DEFAULT_TT_SIZE);
+        pool.stdout(use_stdout);
+        pool.set_thread_count(num_cpus::get().min(MAX_THREADS).max(1));
         PlecoSearcher {
+            pool,
             options: OptionsMap::new(),
             search_mode: SearchType::None,
             board: Board::start_pos(),
             multipv: 1,
+            book: None,
+            own_book: false,
+            book_depth: DEFAULT_BOOK_DEPTH,
+            book_variety: false,
+            skill_level: MAX_SKILL_LEVEL,
+            limit_strength: false,
+            elo: MIN_ELO as u16,
+            contempt: 0,
+            analysis_contempt: AnalysisContempt::Both,
+            show_wdl: false,
+            ponder: false,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            slow_mover: DEFAULT_SLOW_MOVER,
+            nodes_time: 0,
         }
     }
 
+    /// Runs the UCI protocol over standard in and standard out.
     pub fn uci(&mut self) {
-        self.uci_startup();
+        let stdin = io::stdin();
+        self.uci_io(stdin.lock(), io::stdout());
+    }
+
+    /// Runs the UCI protocol, reading commands from `input` and writing everything the engine
+    /// has to say to `output`, which stays the output of the searcher afterwards. If the first
+    /// command is "xboard", the CECP protocol is run instead.
+    ///
+    /// Returns after a "quit" command or at the end of the input, once any search that was
+    /// running has written its best move.
+    pub fn uci_io<R: BufRead, W: Write + Send + 'static>(&mut self, mut input: R, output: W) {
+        self.pool.set_output(Output::new(output));
+        let mut first_command = true;
         let mut full_command = String::new();
         'main: loop {
             full_command.clear();
-            io::stdin().read_line(&mut full_command).ok().unwrap();
+            match input.read_line(&mut full_command) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            // A search ends on its own once it runs out of depth or time.
+            if !self.pool.is_searching() {
+                self.search_mode = SearchType::None;
+            }
             let args: Vec<&str> = full_command.split_whitespace().collect();
             let command: &str = args.first().unwrap_or(&"");
+            if command == "xboard" && first_command {
+                return xboard::run(self, input);
+            }
             match command {
                 "" => continue,
                 "uci" => self.uci_startup(),
                 "setoption" => self.apply_option(&full_command),
-                "options" | "alloptions" => self.options.display_all(),
-                "ucinewgame" => self.clear_search(),
-                "isready" => println!("readyok"),
-                "position" => {
-                    if let Some(b) = parse::position_parse_board(&args[1..]) {
-                        self.board = b;
+                "options" | "alloptions" => self.out(&self.options.display_string()),
+                "ucinewgame" => {
+                    if self.is_searching() {
+                        self.out("unable to start a new game while searching");
                     } else {
-                        println!("unable to parse board");
-                    }
-                }
-                "setboard" => {
-                    if let Some(b) = parse::setboard_parse_board(&args[1..]) {
-                        self.board = b;
-                    } else {
-                        println!("unable to parse board");
+                        self.clear_search();
                     }
                 }
+                "isready" => self.out("readyok"),
+                "position" => self.set_board(parse::position_parse_board(&args[1..])),
+                "setboard" => self.set_board(parse::setboard_parse_board(&args[1..])),
                 "go" => self.uci_go(&args[1..]),
-                "quit" => {
-                    self.halt();
-                    break;
-                }
+                "quit" => break,
                 "stop" => self.halt(),
-                "eval" => Evaluation::trace(&self.board),
-                _ => print!("Unknown Command: {}", full_command),
+                "ponderhit" => self.pool.ponderhit(),
+                "eval" => self.out(&Evaluation::trace_string(&self.board)),
+                _ => self.out(&format!("Unknown Command: {}", full_command.trim())),
             }
+            first_command = false;
             self.apply_all_options();
         }
+        self.halt();
+        self.pool.wait_for_finish();
+    }
+
+    /// Writes a line to the output of the searcher.
+    fn out(&self, text: &str) {
+        self.pool.output.line(text);
+    }
+
+    /// Replaces the current board with a newly parsed one, as long as it is a legal position.
+    fn set_board(&mut self, board: Option<Board>) {
+        match board {
+            Some(b) => match b.is_okay() {
+                Ok(()) => self.board = b,
+                Err(err) => self.out(&format!("illegal position: {:?}", err)),
+            },
+            None => self.out("unable to parse board"),
+        }
     }
 
+    /// Clears the transposition table and the history of the threads, waiting for a
+    /// stopped search to finish first.
     pub fn clear_search(&mut self) {
+        self.pool.wait_for_finish();
         self.clear_tt();
-        threadpool().clear_all();
+        self.pool.timer.clear();
+        self.pool.clear_all();
     }
 
     fn uci_go(&mut self, args: &[&str]) {
         let limit = parse::parse_time(&args);
-        threadpool().uci_search(&self.board, &limit.create())
+        let limits = match self.search_limits(&self.board, &limit) {
+            Ok(limits) => limits,
+            Err(err) => {
+                self.out(&err.to_string());
+                return;
+            }
+        };
+        if !limit.infinite && !limit.ponder && limit.search_moves.is_none() {
+            if let Some(mov) = self.book_move() {
+                self.out(&format!("bestmove {}", self.board.uci_move(mov)));
+                return;
+            }
+        }
+        // Work kept back during the last search is done before starting the next one.
+        self.apply_all_options();
+        self.search_mode = SearchType::Search;
+        self.pool.uci_search(&self.board, &limits)
+    }
+
+    /// Creates the `Limits` of a search of `board`, restricted to the moves of
+    /// `limit.search_moves` if there are any. Fails if those moves can't be searched.
+    fn search_limits(&self, board: &Board, limit: &PreLimits) -> Result<Limits, SearchMovesError> {
+        let search_moves = match limit.search_moves {
+            Some(ref moves) => parse::parse_search_moves(board, moves)?.into(),
+            None => Vec::new(),
+        };
+        let mut limits = self.create_limits(limit.clone(), board.turn());
+        limits.search_moves = search_moves;
+        Ok(limits)
+    }
+
+    /// Creates the `Limits` of a search, applying the options that affect the search.
+    /// `turn` is the side to move at the root, which is the side the engine plays.
+    fn create_limits(&self, limit: PreLimits, turn: Player) -> Limits {
+        let analysis = limit.infinite;
+        let mut limits = limit.create();
+        limits.multi_pv = self.multipv;
+        limits.show_wdl = self.show_wdl;
+        limits.use_ponder = self.ponder;
+        limits.move_overhead = self.move_overhead;
+        limits.slow_mover = self.slow_mover;
+        limits.nodes_time = self.nodes_time;
+        limits.skill_level = if self.limit_strength {
+            skill::level_from_elo(self.elo)
+        } else {
+            self.skill_level
+        };
+        // A reduced skill level needs a few alternatives to the best move to choose from.
+        if limits.skill_level < MAX_SKILL_LEVEL {
+            limits.multi_pv = limits.multi_pv.max(SKILL_MULTI_PV);
+        }
+        // While analyzing, contempt can be kept to one side, whoever is to move.
+        limits.contempt = match (analysis, self.analysis_contempt) {
+            (true, AnalysisContempt::Off) => 0,
+            (true, AnalysisContempt::White) if turn == Player::Black => -self.contempt,
+            (true, AnalysisContempt::Black) if turn == Player::White => -self.contempt,
+            _ => self.contempt,
+        };
+        limits
+    }
+
+    /// Returns a move from the opening book for the current board, if the book is
+    /// enabled and the position is in it.
+    fn book_move(&self) -> Option<BitMove> {
+        if !self.own_book || self.board.moves_played() / 2 >= self.book_depth {
+            return None;
+        }
+        let book = self.book.as_ref()?;
+        if self.book_variety {
+            book.weighted_move(&self.board)
+        } else {
+            book.best_move(&self.board)
+        }
+    }
+
+    fn load_book(&mut self, path: &str) {
+        if path == NO_BOOK_FILE {
+            self.book = None;
+            return;
+        }
+        match PolyglotBook::open(path) {
+            Ok(book) => self.book = Some(book),
+            Err(e) => {
+                self.book = None;
+                self.out(&format!("unable to load book '{}': {}", path, e));
+            }
+        }
     }
 
     fn apply_option(&mut self, full_command: &str) {
@@ -106,11 +268,11 @@ impl PlecoSearcher {
         args.next().unwrap(); // setoption
         if let Some(non_name) = args.next() {
             if non_name != "name" {
-                println!("setoption [name]");
+                self.out("setoption [name]");
                 return;
             }
         } else {
-            println!("setoption name [name] ");
+            self.out("setoption name [name] ");
             return;
         }
         let mut name = String::new();
@@ -120,7 +282,7 @@ impl PlecoSearcher {
             //[should be name of the option]
             name += third_arg;
         } else {
-            println!("setoption name [name]");
+            self.out("setoption name [name]");
             return;
         }
 
@@ -132,7 +294,7 @@ impl PlecoSearcher {
                     .trim()
                     .to_string();
                 if &value == "" {
-                    println!("forgot a value!");
+                    self.out("forgot a value!");
                     return;
                 }
                 break 'nv;
@@ -143,50 +305,81 @@ impl PlecoSearcher {
         }
 
         if !self.options.apply_option(&name, &value) {
-            println!("unable to apply option: '{}'", full_command);
+            self.out(&format!(
+                "unable to apply option: '{}'",
+                full_command.trim()
+            ));
         } else {
             self.apply_all_options();
         }
     }
 
+    /// Applies the work of the options that have been set. Work that can't be done while
+    /// searching is kept until the search has ended.
     fn apply_all_options(&mut self) {
+        let mut deferred = Vec::new();
         while let Some(work) = self.options.work() {
-            if self.is_searching() && !work.usable_while_searching() {
-                println!("unable to apply work");
-            } else {
-                match work {
-                    OptionWork::ClearTT => self.clear_tt(),
-                    OptionWork::ResizeTT(mb) => self.resize_tt(mb),
-                    OptionWork::MultiPV(npv) => self.multipv = npv,
-                    OptionWork::Threads(num) => threadpool().set_thread_count(num),
+            if !work.usable_while_searching() {
+                if self.is_searching() {
+                    deferred.push(work);
+                    continue;
                 }
+                // A stopped search may still be using the threads and the table.
+                self.pool.wait_for_finish();
+            }
+            match work {
+                OptionWork::ClearTT => self.clear_tt(),
+                OptionWork::ResizeTT(mb) => self.resize_tt(mb),
+                OptionWork::MultiPV(npv) => self.multipv = npv,
+                OptionWork::Threads(num) => self.pool.set_thread_count(num),
+                OptionWork::OwnBook(own_book) => self.own_book = own_book,
+                OptionWork::BookFile(path) => self.load_book(&path),
+                OptionWork::BookDepth(depth) => self.book_depth = depth,
+                OptionWork::BookVariety(variety) => self.book_variety = variety,
+                OptionWork::Ponder(ponder) => self.ponder = ponder,
+                OptionWork::SkillLevel(level) => self.skill_level = level,
+                OptionWork::LimitStrength(limit) => self.limit_strength = limit,
+                OptionWork::Elo(elo) => self.elo = elo,
+                OptionWork::Contempt(contempt) => self.contempt = contempt,
+                OptionWork::AnalysisContempt(side) => self.analysis_contempt = side,
+                OptionWork::ShowWDL(show) => self.show_wdl = show,
+                OptionWork::MoveOverhead(overhead) => self.move_overhead = overhead,
+                OptionWork::SlowMover(slow_mover) => self.slow_mover = slow_mover,
+                OptionWork::NodesTime(nodes) => self.nodes_time = nodes,
             }
         }
+        self.options.work.extend(deferred);
     }
 
     fn uci_startup(&self) {
-        println!("id name {}", ID_NAME);
-        println!("id authors {}", ID_AUTHORS);
-        self.options.display_all();
-        println!("uciok");
+        self.out(&format!("id name {}", ID_NAME));
+        self.out(&format!("id authors {}", ID_AUTHORS));
+        self.out(&self.options.display_string());
+        self.out("uciok");
     }
 
-    pub fn search(&mut self, board: &Board, limit: &PreLimits) {
+    /// Starts searching `board` in the background. The search is restricted to the moves of
+    /// `limit.search_moves` if given, which fails without searching if the list is empty or
+    /// holds a move that isn't legal.
+    pub fn search(&mut self, board: &Board, limit: &PreLimits) -> Result<(), SearchMovesError> {
+        let limits = self.search_limits(board, limit)?;
         self.search_mode = SearchType::Search;
-        threadpool().uci_search(board, &(limit.clone().create()));
+        self.pool.uci_search(board, &limits);
+        Ok(())
     }
 
     pub fn halt(&mut self) {
         self.search_mode = SearchType::None;
-        threadpool().set_stop(true);
+        self.pool.ponder.store(false, Ordering::Relaxed);
+        self.pool.set_stop(true);
     }
 
     pub fn stop_search_get_move(&mut self) -> BitMove {
-        self.search_mode = SearchType::None;
         if self.is_searching() {
-            threadpool().set_stop(true);
-            threadpool().wait_for_finish();
-            threadpool().best_move()
+            self.search_mode = SearchType::None;
+            self.pool.set_stop(true);
+            self.pool.wait_for_finish();
+            self.pool.best_move()
         } else {
             return BitMove::null();
         }
@@ -195,14 +388,32 @@ impl PlecoSearcher {
     pub fn await_move(&mut self) -> BitMove {
         if self.is_searching() {
             return {
-                threadpool().wait_for_finish();
-                threadpool().best_move()
+                self.pool.wait_for_finish();
+                self.pool.best_move()
             };
         } else {
             return BitMove::null();
         }
     }
 
+    /// Waits for the search to finish, and returns its result. Returns `None` if there is
+    /// no search to wait for.
+    pub fn await_result(&mut self) -> Option<SearchResult> {
+        if self.is_searching() {
+            self.pool.wait_for_finish();
+            Some(self.pool.search_result())
+        } else {
+            None
+        }
+    }
+
+    /// Sets where the information of each iteration of a search is sent to, in addition to
+    /// stdout if that is used. Waits for the current search to finish first.
+    pub fn set_info_sink(&mut self, sink: Option<Box<InfoSink>>) {
+        self.pool.wait_for_finish();
+        self.pool.info_sink = sink;
+    }
+
     pub fn is_searching(&self) -> bool {
         if self.search_mode == SearchType::None {
             return false;
@@ -211,33 +422,244 @@ impl PlecoSearcher {
     }
 
     pub fn hash_percent(&self) -> f64 {
-        tt().hash_percent()
+        self.pool.tt.hash_percent()
     }
 
     pub fn clear_tt(&mut self) {
-        unsafe { tt().clear() };
+        unsafe { self.pool.tt.clear() };
     }
 
     pub fn resize_tt(&mut self, mb: usize) {
-        unsafe { tt().resize_to_megabytes(mb) };
+        unsafe { self.pool.tt.resize_to_megabytes(mb) };
     }
 
     pub fn use_stdout(&mut self, stdout: bool) {
-        threadpool().stdout(stdout);
+        self.pool.stdout(stdout);
+    }
+
+    /// Returns the `ThreadPool` owned by this searcher.
+    pub fn threadpool(&mut self) -> &mut ThreadPool {
+        &mut self.pool
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pleco::board::polyglot::BookEntry;
+    use search::info::{SearchInfo, SearchScore};
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+
+    // Keeps the results passed to the sink.
+    struct Results(Arc<Mutex<Vec<SearchResult>>>);
+
+    impl InfoSink for Results {
+        fn info(&self, _info: &SearchInfo) {}
+
+        fn result(&self, result: &SearchResult) {
+            self.0.lock().unwrap().push(result.clone());
+        }
+    }
 
     fn ply_3() {
         let mut limit = PreLimits::blank();
         limit.depth = Some(3);
         let board = Board::start_pos();
         let mut s = PlecoSearcher::init(false);
-        s.search(&board, &limit);
+        s.search(&board, &limit).unwrap();
         s.await_move();
     }
 
+    #[test]
+    fn book_moves() {
+        let start = Board::start_pos();
+        let mut open_game = Board::start_pos();
+        open_game.apply_uci_move("e2e4");
+        open_game.apply_uci_move("e7e5");
+
+        // e2e4, d2d4 and g1f3, in Polyglot move encoding
+        let entries = [
+            (start.polyglot_key(), 12 << 6 | 28, 1),
+            (start.polyglot_key(), 11 << 6 | 27, 2),
+            (open_game.polyglot_key(), 6 << 6 | 21, 1),
+        ];
+        let mut bytes = Vec::new();
+        for &(key, mov, weight) in entries.iter() {
+            let entry = BookEntry {
+                key,
+                mov,
+                weight,
+                learn: 0,
+            };
+            bytes.extend_from_slice(&entry.to_bytes());
+        }
+
+        let mut s = PlecoSearcher::init(false);
+        s.book = Some(PolyglotBook::from_bytes(&bytes));
+        assert!(s.book_move().is_none());
+
+        assert!(s.options.apply_option("OwnBook", "true"));
+        s.apply_all_options();
+        assert_eq!(s.book_move().unwrap().stringify(), "d2d4");
+
+        s.book_variety = true;
+        let mov = s.book_move().unwrap().stringify();
+        assert!(mov == "d2d4" || mov == "e2e4");
+
+        s.board = open_game;
+        assert_eq!(s.book_move().unwrap().stringify(), "g1f3");
+        assert!(s.options.apply_option("Book Depth", "1"));
+        s.apply_all_options();
+        assert!(s.book_move().is_none());
+    }
+
+    #[test]
+    fn contempt_limits() {
+        let mut s = PlecoSearcher::init(false);
+        assert!(s.options.apply_option("Contempt", "30"));
+        assert!(!s.options.apply_option("Analysis Contempt", "Neither"));
+        assert!(s.options.apply_option("Analysis Contempt", "White"));
+        s.apply_all_options();
+
+        let mut limit = PreLimits::blank();
+        limit.depth = Some(5);
+        assert_eq!(s.create_limits(limit.clone(), Player::Black).contempt, 30);
+
+        limit.infinite = true;
+        assert_eq!(s.create_limits(limit.clone(), Player::White).contempt, 30);
+        assert_eq!(s.create_limits(limit.clone(), Player::Black).contempt, -30);
+
+        assert!(s.options.apply_option("Analysis Contempt", "Off"));
+        s.apply_all_options();
+        assert_eq!(s.create_limits(limit, Player::White).contempt, 0);
+    }
+
+    #[test]
+    fn search_limits() {
+        let mut s = PlecoSearcher::init(false);
+
+        let board =
+            Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+                .unwrap();
+        let mut limit = PreLimits::blank();
+        limit.mate = Some(1);
+        s.search(&board, &limit).unwrap();
+        assert_eq!(s.await_move().stringify(), "h5f7");
+
+        let mut limit = PreLimits::blank();
+        limit.nodes = Some(5000);
+        s.search(&Board::start_pos(), &limit).unwrap();
+        s.await_move();
+        let nodes = s.pool.nodes();
+        assert!(nodes >= 5000 && nodes < 10000);
+
+        // There is no move to return from a finished game
+        let checkmate =
+            Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        for board in [checkmate, stalemate].iter() {
+            let mut limit = PreLimits::blank();
+            limit.depth = Some(5);
+            s.search(board, &limit).unwrap();
+            assert!(s.await_move().is_null());
+        }
+
+        // Only the moves of searchmoves are searched, which have to be legal.
+        let mut limit = PreLimits::blank();
+        limit.depth = Some(3);
+        limit.search_moves = Some(vec!["h5f3".to_string(), "h5h6".to_string()]);
+        s.search(&board, &limit).unwrap();
+        assert_eq!(s.await_move().stringify(), "h5f3");
+        limit.search_moves = Some(vec!["h5f3".to_string(), "h5h8".to_string()]);
+        assert_eq!(
+            s.search(&board, &limit),
+            Err(SearchMovesError::Illegal("h5h8".to_string()))
+        );
+        limit.search_moves = Some(Vec::new());
+        assert_eq!(s.search(&board, &limit), Err(SearchMovesError::Empty));
+    }
+
+    #[test]
+    fn multi_pv_mate_limit() {
+        let mut s = PlecoSearcher::init(false);
+        assert!(s.options.apply_option("MultiPV", "3"));
+        s.apply_all_options();
+
+        // Only the first line mates in two, and the search stops once it is proven.
+        let board =
+            Board::from_fen("r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1")
+                .unwrap();
+        let mut limit = PreLimits::blank();
+        limit.mate = Some(2);
+        s.search(&board, &limit).unwrap();
+        let result = s.await_result().unwrap();
+        assert_eq!(result.best_move.stringify(), "d5f6");
+        assert_eq!(result.score, SearchScore::Mate(2));
+        assert!(result.depth < 20);
+    }
+
+    #[test]
+    fn independent_searchers() {
+        let mut a = PlecoSearcher::init(false);
+        let mut b = PlecoSearcher::init(false);
+        assert!(a.options.apply_option("Hash", "1"));
+        assert!(b.options.apply_option("Threads", "2"));
+        a.apply_all_options();
+        b.apply_all_options();
+        assert!(a.pool.tt.size_megabytes() < b.pool.tt.size_megabytes());
+        assert_eq!(b.pool.size(), 2);
+
+        // Both searches run at the same time, and stopping one leaves the other running.
+        let mut limit = PreLimits::blank();
+        limit.infinite = true;
+        a.search(&Board::start_pos(), &limit).unwrap();
+        let mut limit = PreLimits::blank();
+        limit.depth = Some(4);
+        let mated = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        b.search(&mated, &limit).unwrap();
+        assert_eq!(b.await_move().stringify(), "a1a8");
+        assert!(!a.pool.stop.load(Ordering::Relaxed));
+        assert!(!a.stop_search_get_move().is_null());
+    }
+
+    #[test]
+    fn search_results() {
+        let mut s = PlecoSearcher::init(false);
+        let (sender, receiver) = mpsc::channel();
+        s.set_info_sink(Some(Box::new(Mutex::new(sender))));
+
+        let board =
+            Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4")
+                .unwrap();
+        let mut limit = PreLimits::blank();
+        limit.depth = Some(5);
+        assert!(s.options.apply_option("MultiPV", "2"));
+        s.apply_all_options();
+        s.search(&board, &limit).unwrap();
+        let result = s.await_result().unwrap();
+        assert_eq!(result.best_move.stringify(), "h5f7");
+        assert_eq!(result.score, SearchScore::Mate(1));
+        assert_eq!(result.lines.len(), 2);
+        assert_eq!(result.lines[0].pv[0], result.best_move);
+        assert!(result.depth >= 1 && result.nodes > 0);
+
+        let infos: Vec<SearchInfo> = receiver.try_iter().collect();
+        assert!(!infos.is_empty());
+        assert!(infos.iter().all(|info| !info.lines.is_empty()));
+
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        s.search(&stalemate, &limit).unwrap();
+        let result = s.await_result().unwrap();
+        assert!(result.best_move.is_null());
+        assert_eq!(result.score, SearchScore::Cp(0));
+
+        // The sink gets the result of a finished game as well.
+        let results = Arc::new(Mutex::new(Vec::new()));
+        s.set_info_sink(Some(Box::new(Results(results.clone()))));
+        s.search(&stalemate, &limit).unwrap();
+        s.await_move();
+        assert_eq!(*results.lock().unwrap(), vec![result]);
+    }
 }
@@ -37,5 +37,6 @@ pub mod tables;
 pub mod threadpool;
 pub mod time;
 pub mod uci;
+pub mod xboard;
 
 pub use consts::*;
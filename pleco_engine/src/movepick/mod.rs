@@ -280,7 +280,11 @@ impl MovePicker {
                 while self.cur_ptr < self.end_ptr {
                     mov = self.pick_best(self.cur_ptr, self.end_ptr);
                     unsafe {self.cur_ptr = self.cur_ptr.add(1);}
-                    if mov.bit_move != self.ttm && mov.score > -128 {
+                    // The tt move has already been returned, so it isn't kept as a bad capture.
+                    if mov.bit_move == self.ttm {
+                        continue;
+                    }
+                    if mov.score > -128 {
                         let previous_val = unsafe {
                             (*self.cur_ptr.sub(1)).score as i32
                         };
@@ -688,6 +692,14 @@ mod tests {
         movepick_main_search(b, ttm, &killers, cm, depth);
     }
 
+    #[test]
+    fn movepick_losing_capture_ttm() {
+        // The tt move Qxf7# loses the queen by SEE, and used to be returned again as a bad capture.
+        let b = Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4").unwrap();
+        let ttm = b.generate_moves().iter().cloned().find(|m| m.stringify() == "h5f7").unwrap();
+        movepick_main_search(b, ttm, &[BitMove::null(); 2], BitMove::null(), 5);
+    }
+
 
 
     fn movepick_rand_one(b: Board) {
@@ -8,15 +8,23 @@ use std::cmp::Ordering as CmpOrder;
 use pleco::core::score::*;
 use pleco::BitMove;
 
+use consts::MAX_PLY;
+
 const MAX_MOVES: usize = 256;
 
+/// The maximum length of a principal variation.
+pub const MAX_PV_LEN: usize = MAX_PLY as usize + 1;
+
 /// Keeps track of information of a move for the position to be searched.
-#[derive(Copy, Clone, Eq)]
+#[derive(Copy, Clone)]
 pub struct RootMove {
     pub score: i32,
     pub prev_score: i32,
     pub bit_move: BitMove,
     pub depth_reached: i16,
+    pub sel_depth: i16,
+    pv: [BitMove; MAX_PV_LEN],
+    pv_len: usize,
 }
 
 impl RootMove {
@@ -28,6 +36,28 @@ impl RootMove {
             score: NEG_INFINITE as i32,
             prev_score: NEG_INFINITE as i32,
             depth_reached: 0,
+            sel_depth: 0,
+            pv: [bit_move; MAX_PV_LEN],
+            pv_len: 1,
+        }
+    }
+
+    /// Returns the principal variation of this move, starting with the move itself.
+    #[inline]
+    pub fn pv(&self) -> &[BitMove] {
+        &self.pv[..self.pv_len]
+    }
+
+    /// Sets the principal variation to this move, followed by the null-terminated
+    /// continuation `line`.
+    pub fn update_pv(&mut self, line: &[BitMove]) {
+        self.pv_len = 1;
+        for mov in line.iter().take_while(|m| !m.is_null()) {
+            if self.pv_len == MAX_PV_LEN {
+                break;
+            }
+            self.pv[self.pv_len] = *mov;
+            self.pv_len += 1;
         }
     }
 
@@ -84,3 +114,5 @@ impl PartialEq for RootMove {
         self.score == other.score && self.prev_score == other.prev_score
     }
 }
+
+impl Eq for RootMove {}
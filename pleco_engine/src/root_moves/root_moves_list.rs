@@ -71,6 +71,13 @@ impl RootMoveList {
         self.iter_mut().for_each(|b| b.prev_score = b.score);
     }
 
+    /// Sorts the `RootMove`s from index `start` up to (but not including) index `end`,
+    /// leaving the rest of the list in place.
+    #[inline]
+    pub fn sort_range(&mut self, start: usize, end: usize) {
+        (**self)[start..end].sort();
+    }
+
     /// Returns the first `RootMove` in the list.
     ///
     /// # Safety
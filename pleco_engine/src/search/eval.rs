@@ -418,16 +418,29 @@ impl Tracing for Trace {
 pub struct Evaluation {}
 
 impl Evaluation {
-    pub fn evaluate(board: &Board, pawn_table: &mut PawnTable, material: &mut Material) -> Value {
+    /// Evaluates the board from the perspective of the side to move. `contempt` is a white
+    /// relative bonus, favoring the side the engine plays to avoid simplifying into a draw.
+    pub fn evaluate(
+        board: &Board,
+        pawn_table: &mut PawnTable,
+        material: &mut Material,
+        contempt: Score,
+    ) -> Value {
         let pawn_entry = { pawn_table.probe(&board) };
         let material_entry = { material.probe(&board) };
         let mut no_trace = NoTrace::new();
         let mut eval =
             EvaluationInner::<NoTrace>::new(board, pawn_entry, material_entry, &mut no_trace);
-        eval.value()
+        eval.value(contempt)
     }
 
+    /// Prints a breakdown of the evaluation of the board.
     pub fn trace(board: &Board) {
+        println!("{}", Evaluation::trace_string(board));
+    }
+
+    /// Returns a breakdown of the evaluation of the board, by each term of the evaluation.
+    pub fn trace_string(board: &Board) -> String {
         let mut pawn_table = PawnTable::new();
         let mut material = Material::new();
         let pawn_entry = { pawn_table.probe(&board) };
@@ -436,23 +449,17 @@ impl Evaluation {
         let mut total = {
             let mut eval =
                 EvaluationInner::<Trace>::new(board, pawn_entry, material_entry, &mut trace);
-            eval.value()
+            eval.value(Score::ZERO)
         };
         if board.turn() == Player::Black {
             total = -total;
         }
-        print!("{}", trace.t);
-        if trace.t.used {
-            println!(
-                "Total evaluation: {:6.3}  (white side)",
-                total as f64 / PAWN_EG as f64
-            );
-        } else {
-            println!(
-                "Total evaluation: {:6.3}  (white side) (lazy)",
-                total as f64 / PAWN_EG as f64
-            );
-        }
+        format!(
+            "{}Total evaluation: {:6.3}  (white side){}",
+            trace.t,
+            total as f64 / PAWN_EG as f64,
+            if trace.t.used { "" } else { " (lazy)" }
+        )
     }
 }
 
@@ -494,11 +501,12 @@ impl<'a, 'b, T: Tracing> EvaluationInner<'a, 'b, T> {
         }
     }
 
-    fn value(&mut self) -> Value {
+    fn value(&mut self, contempt: Score) -> Value {
         let mut score = self.pawn_entry.pawns_score(Player::White)
             - self.pawn_entry.pawns_score(Player::Black)
             + self.material_entry.score()
-            + self.board.psq();
+            + self.board.psq()
+            + contempt;
 
         let mut v: i32 = (score.0 + score.1) / 2;
         if v.abs() > LAZY_THRESHOLD {
@@ -0,0 +1,230 @@
+//! Structured results of a search, for using the engine as a library.
+//!
+//! While searching, the main thread reports each iteration as a `SearchInfo`, which is printed
+//! as UCI "info" lines and passed to the `InfoSink` of the searcher, if there is one. Once the
+//! search is over, its outcome is kept as a `SearchResult`, which is passed to the sink as well.
+
+use std::fmt;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+
+use pleco::core::score::*;
+use pleco::{BitMove, Board};
+
+use consts::MAX_PLY;
+
+/// The score of a line, either in centipawns or as the number of moves until mate.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SearchScore {
+    /// The score in centipawns, from the point of view of the side to move.
+    Cp(i32),
+    /// Mate in this many moves. Negative if the side to move is getting mated.
+    Mate(i32),
+}
+
+impl SearchScore {
+    /// Converts an internal search value into a `SearchScore`.
+    pub fn from_value(value: Value) -> Self {
+        if value.abs() < MATE - MAX_PLY as i32 {
+            SearchScore::Cp(value * 100 / PAWN_EG)
+        } else if value > 0 {
+            SearchScore::Mate((MATE - value + 1) / 2)
+        } else {
+            SearchScore::Mate((-MATE - value) / 2)
+        }
+    }
+}
+
+impl fmt::Display for SearchScore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SearchScore::Cp(cp) => write!(f, "cp {}", cp),
+            SearchScore::Mate(moves) => write!(f, "mate {}", moves),
+        }
+    }
+}
+
+/// Whether the score of a line is exact, or only a bound from failing outside of the
+/// aspiration window.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ScoreBound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/// A principal variation found by the search.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PvLine {
+    /// The index of the line, starting from 1 for the best line.
+    pub multi_pv: usize,
+    pub depth: i16,
+    pub sel_depth: i16,
+    pub score: SearchScore,
+    pub bound: ScoreBound,
+    /// The chances of winning, drawing and losing in permille, if `UCI_ShowWDL` is set.
+    pub wdl: Option<(u32, u32, u32)>,
+    pub pv: Vec<BitMove>,
+}
+
+/// The state of a search after an iteration.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchInfo {
+    pub nodes: u64,
+    pub nps: u64,
+    /// How full the transposition table is, in permille.
+    pub hash_full: u32,
+    /// Milliseconds since the start of the search.
+    pub time: u64,
+    pub lines: Vec<PvLine>,
+}
+
+impl SearchInfo {
+    /// Returns a UCI "info" line for each of the principal variations. Moves are written with
+    /// `Board::uci_move`, so castling follows the notation of the board being searched.
+    pub fn uci_lines(&self, board: &Board) -> String {
+        let mut s = String::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                s.push('\n');
+            }
+            s.push_str(&format!(
+                "info depth {} seldepth {} multipv {} score {}",
+                line.depth, line.sel_depth, line.multi_pv, line.score
+            ));
+            if let Some((win, draw, loss)) = line.wdl {
+                s.push_str(&format!(" wdl {} {} {}", win, draw, loss));
+            }
+            match line.bound {
+                ScoreBound::Lower => s.push_str(" lowerbound"),
+                ScoreBound::Upper => s.push_str(" upperbound"),
+                ScoreBound::Exact => {}
+            }
+            s.push_str(&format!(" nodes {}", self.nodes));
+            if self.time > 1000 {
+                s.push_str(&format!(" nps {} hashfull {}", self.nps, self.hash_full));
+            }
+            s.push_str(&format!(" time {} pv", self.time));
+            for mov in line.pv.iter() {
+                s.push(' ');
+                s.push_str(&board.uci_move(*mov));
+            }
+        }
+        s
+    }
+}
+
+/// The outcome of a finished search.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SearchResult {
+    /// The move to play, or a null move if the game is already over.
+    pub best_move: BitMove,
+    /// The expected reply to the best move, to ponder on.
+    pub ponder_move: Option<BitMove>,
+    /// The score of the best move.
+    pub score: SearchScore,
+    /// The deepest iteration that was completed.
+    pub depth: i16,
+    pub sel_depth: i16,
+    pub nodes: u64,
+    /// Milliseconds the search took.
+    pub time: u64,
+    /// Each of the principal variations, best first.
+    pub lines: Vec<PvLine>,
+}
+
+impl SearchResult {
+    /// The result of searching a position with no legal moves, which is a loss if in check
+    /// and a draw otherwise.
+    pub fn game_over(in_check: bool) -> Self {
+        SearchResult {
+            best_move: BitMove::null(),
+            ponder_move: None,
+            score: if in_check {
+                SearchScore::Mate(0)
+            } else {
+                SearchScore::Cp(0)
+            },
+            depth: 0,
+            sel_depth: 0,
+            nodes: 0,
+            time: 0,
+            lines: Vec::new(),
+        }
+    }
+}
+
+/// Receives the `SearchInfo` of each iteration while searching, and the `SearchResult` once
+/// the search is over.
+///
+/// Both are called from the main search thread, so they should return quickly. Closures taking a
+/// `&SearchInfo` are sinks, as are channels wrapped in a `Mutex`.
+pub trait InfoSink: Send + Sync {
+    fn info(&self, info: &SearchInfo);
+
+    /// Called with the result of the search, right before the best move is printed.
+    fn result(&self, _result: &SearchResult) {}
+}
+
+impl<F> InfoSink for F
+where
+    F: Fn(&SearchInfo) + Send + Sync,
+{
+    fn info(&self, info: &SearchInfo) {
+        self(info)
+    }
+}
+
+impl InfoSink for Mutex<Sender<SearchInfo>> {
+    fn info(&self, info: &SearchInfo) {
+        // The receiver might not be interested anymore, which is not a reason to stop searching.
+        if let Ok(sender) = self.lock() {
+            let _ = sender.send(info.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_scores() {
+        assert_eq!(SearchScore::from_value(PAWN_EG), SearchScore::Cp(100));
+        assert_eq!(SearchScore::from_value(-PAWN_EG / 2), SearchScore::Cp(-50));
+        assert_eq!(SearchScore::from_value(MATE - 1), SearchScore::Mate(1));
+        assert_eq!(SearchScore::from_value(MATE - 5), SearchScore::Mate(3));
+        assert_eq!(SearchScore::from_value(-MATE + 4), SearchScore::Mate(-2));
+        assert_eq!(SearchScore::Mate(-2).to_string(), "mate -2");
+    }
+
+    #[test]
+    fn uci_lines() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        let castle = Board::from_fen(fen).unwrap().parse_san("O-O").unwrap();
+        let mut info = SearchInfo {
+            nodes: 1200,
+            nps: 0,
+            hash_full: 0,
+            time: 15,
+            lines: vec![PvLine {
+                multi_pv: 1,
+                depth: 3,
+                sel_depth: 5,
+                score: SearchScore::Cp(20),
+                bound: ScoreBound::Lower,
+                wdl: None,
+                pv: vec![castle],
+            }],
+        };
+        assert_eq!(
+            info.uci_lines(&Board::from_fen(fen).unwrap()),
+            "info depth 3 seldepth 5 multipv 1 score cp 20 lowerbound nodes 1200 time 15 pv e1g1"
+        );
+        info.lines[0].bound = ScoreBound::Exact;
+        assert_eq!(
+            info.uci_lines(&Board::from_chess960_fen(fen).unwrap()),
+            "info depth 3 seldepth 5 multipv 1 score cp 20 nodes 1200 time 15 pv e1h1"
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! The main searching function.
 
 pub mod eval;
+pub mod info;
+pub mod skill;
 
 use std::cell::UnsafeCell;
 use std::cmp::{max, min};
@@ -8,6 +10,7 @@ use std::mem;
 use std::ptr;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
+use pleco::core::masks::PLAYER_CNT;
 use pleco::core::piece_move::MoveType;
 use pleco::core::score::*;
 use pleco::core::*;
@@ -24,14 +27,19 @@ use {MAX_PLY, THREAD_STACK_SIZE};
 use consts::*;
 use movepick::MovePicker;
 use root_moves::root_moves_list::RootMoveList;
-use root_moves::RootMove;
+use root_moves::{RootMove, MAX_PV_LEN};
 use sync::{GuardedBool, LockLatch};
 use tables::material::Material;
 use tables::pawn_table::PawnTable;
 use tables::prelude::*;
-use threadpool::threadpool;
+use threadpool::ThreadPool;
 use time::time_management::TimeManager;
 use time::uci_timer::*;
+use uci::output::Output;
+use uci::wdl;
+
+use self::info::{PvLine, ScoreBound, SearchInfo, SearchResult, SearchScore};
+use self::skill::Skill;
 
 const RAZORING_MARGIN: i32 = 590;
 
@@ -84,7 +92,7 @@ pub fn init() {
 }
 
 pub struct Stack {
-    pv: BitMove,
+    pv: [BitMove; MAX_PV_LEN],
     cont_history: *mut PieceToHistory,
     ply: u16,
     current_move: BitMove,
@@ -108,6 +116,21 @@ impl Stack {
     pub fn incr(&mut self) -> &mut Stack {
         self.offset(1)
     }
+
+    /// Sets the principal variation of this ply to a move, followed by the principal
+    /// variation of the next ply.
+    pub fn update_pv(&mut self, mov: BitMove) {
+        self.pv[0] = mov;
+        let mut i: usize = 1;
+        while i < MAX_PV_LEN {
+            let next = self.incr().pv[i - 1];
+            self.pv[i] = next;
+            if next.is_null() {
+                break;
+            }
+            i += 1;
+        }
+    }
 }
 
 /// A Stack for the searcher, with information being contained per-ply.
@@ -140,19 +163,22 @@ pub struct Searcher {
     pub kill: AtomicBool,
     pub searching: Arc<GuardedBool>,
     pub cond: Arc<LockLatch>,
+    pool: *const ThreadPool,
 
     // search data
     pub depth_completed: i16,
     pub limit: Limits,
     pub board: Board,
-    pub time_man: &'static TimeManager,
     pub pawns: PawnTable,
     pub material: Material,
     pub root_moves: UnsafeCell<RootMoveList>,
+    pub pv_idx: usize,
     pub selected_depth: i16,
     pub last_best_move: BitMove,
     pub last_best_move_depth: i16,
     pub nodes: AtomicU64,
+    pub contempt: Score,
+    pub draw_value: [Value; PLAYER_CNT],
 
     pub counter_moves: CounterMoveHistory,
     pub main_history: ButterflyHistory,
@@ -165,30 +191,36 @@ pub struct Searcher {
     pub failed_low: bool,
     pub best_move_changes: f64,
     pub previous_time_reduction: f64,
+    pub skill: Skill,
+    pub result: SearchResult,
 }
 
 unsafe impl Send for Searcher {}
 unsafe impl Sync for Searcher {}
 
 impl Searcher {
-    /// Creates a new `Searcher` of an ID and condition to be released by.
-    pub fn new(id: usize, cond: Arc<LockLatch>) -> Self {
+    /// Creates a new `Searcher` of an ID and condition to be released by, belonging to the
+    /// given `ThreadPool`.
+    pub fn new(id: usize, cond: Arc<LockLatch>, pool: *const ThreadPool) -> Self {
         Searcher {
             id,
             kill: AtomicBool::new(false),
             searching: Arc::new(GuardedBool::new(true)),
             cond,
+            pool,
             depth_completed: 0,
             limit: Limits::blank(),
             board: Board::start_pos(),
-            time_man: timer(),
             pawns: PawnTable::new(),
             material: Material::new(),
             root_moves: UnsafeCell::new(RootMoveList::new()),
+            pv_idx: 0,
             selected_depth: 0,
             last_best_move: BitMove::null(),
             last_best_move_depth: 0,
             nodes: AtomicU64::new(0),
+            contempt: Score::ZERO,
+            draw_value: [DRAW; PLAYER_CNT],
             counter_moves: CounterMoveHistory::new(),
             main_history: ButterflyHistory::new(),
             capture_history: CapturePieceToHistory::new(),
@@ -198,6 +230,8 @@ impl Searcher {
             failed_low: false,
             best_move_changes: 0.0,
             previous_time_reduction: 0.0,
+            skill: Skill::new(MAX_SKILL_LEVEL),
+            result: SearchResult::game_over(false),
         }
     }
 
@@ -220,6 +254,11 @@ impl Searcher {
             if self.kill.load(Ordering::SeqCst) {
                 return;
             }
+            // The main thread has its own condition, so it can reset it before searching. This
+            // guarantees only one search is started per call to `ThreadPool::uci_search`.
+            if self.main_thread() {
+                self.cond.lock();
+            }
             self.go();
         }
     }
@@ -242,27 +281,45 @@ impl Searcher {
     fn main_thread_go(&mut self) {
         // set the global limit
 
+        self.skill = Skill::new(self.limit.skill_level);
+
         // Increment the TT search table.
-        tt().new_search();
+        self.tt().new_search();
         // Start each of the threads!
-        threadpool().thread_cond.set();
+        self.threadpool().thread_cond.set();
 
         // Search ourselves
         self.search_root();
 
+        // While pondering or searching infinitely, the best move can't be sent before the GUI
+        // sends "ponderhit" or "stop", even if the search has already finished. The latch is
+        // locked before checking, so a stop or ponderhit after the check still wakes us up.
+        loop {
+            self.threadpool().stop_cond.lock();
+            let hold = self.threadpool().ponder.load(Ordering::Relaxed) || self.limit.infinite;
+            if self.stop() || !hold {
+                break;
+            }
+            self.threadpool().stop_cond.wait();
+        }
+
         // Lock the other threads condition variable
-        threadpool().thread_cond.lock();
+        self.threadpool().thread_cond.lock();
         // Tell the threads to stop
-        threadpool().set_stop(true);
+        self.threadpool().set_stop(true);
         // Wait for all the non-main threads to finish searching.
-        threadpool().wait_for_non_main();
+        self.threadpool().wait_for_non_main();
+        self.time_man().spend_nodes(self.threadpool().nodes());
 
         // iterate through each thread, and find the best move available (based on score)
         let mut best_move = self.root_moves().first().bit_move;
         let mut best_score = self.root_moves().first().score;
-        if !self.limit.limits_type.is_depth() {
+        let mut pv_reply = self.root_moves().first().pv().get(1).cloned();
+        let mut best_depth = self.depth_completed;
+        let mut best_lines: Option<Vec<PvLine>> = None;
+        if !self.limit.limits_type.is_depth() && self.limit.multi_pv == 1 && !self.skill.enabled() {
             let mut best_thread: &Searcher = &self;
-            threadpool()
+            self.threadpool()
                 .threads
                 .iter()
                 .map(|u| unsafe { &**u.get() })
@@ -276,19 +333,87 @@ impl Searcher {
                 });
             best_move = best_thread.root_moves().first().bit_move;
             best_score = best_thread.root_moves().first().score;
+            pv_reply = best_thread.root_moves().first().pv().get(1).cloned();
+            best_depth = best_thread.depth_completed;
 
             // Cases where the MainTHread did not have the correct best move, display it.
-            if self.use_stdout() && best_thread.id != self.id {
+            if best_thread.id != self.id {
                 best_thread.pv(best_thread.depth_completed, NEG_INFINITE, INFINITE);
+                best_lines = Some(
+                    best_thread
+                        .search_info(best_thread.depth_completed, NEG_INFINITE, INFINITE)
+                        .lines,
+                );
+            }
+        }
+
+        // At a reduced skill level, play the picked move rather than the best one.
+        if self.skill.enabled() {
+            let multi_pv = self.limit.multi_pv;
+            let root_moves = unsafe { &mut *self.root_moves.get() };
+            let picked = self.skill.best_move(root_moves, multi_pv);
+            if let Some(i) = root_moves.iter().position(|rm| rm.bit_move == picked) {
+                root_moves.swap(0, i);
             }
+            best_move = root_moves.first().bit_move;
+            best_score = root_moves.first().score;
+            pv_reply = root_moves.first().pv().get(1).cloned();
         }
 
         self.previous_score = best_score;
         self.best_move = best_move;
 
-        if self.use_stdout() {
-            println!("bestmove {}", best_move.to_string());
+        let ponder_move = self.ponder_move(best_move, pv_reply);
+        let lines = best_lines.unwrap_or_else(|| {
+            self.search_info(self.depth_completed, NEG_INFINITE, INFINITE)
+                .lines
+        });
+        self.result = SearchResult {
+            best_move,
+            ponder_move,
+            score: SearchScore::from_value(best_score),
+            depth: best_depth,
+            sel_depth: lines.first().map_or(0, |line| line.sel_depth),
+            nodes: self.threadpool().nodes(),
+            time: self.elapsed() as u64,
+            lines,
+        };
+
+        if let Some(sink) = self.threadpool().info_sink.as_ref() {
+            sink.result(&self.result);
         }
+
+        let best_move_line = if let Some(ponder_move) = ponder_move {
+            format!(
+                "bestmove {} ponder {}",
+                self.board.uci_move(best_move),
+                self.board.uci_move(ponder_move)
+            )
+        } else {
+            format!("bestmove {}", self.board.uci_move(best_move))
+        };
+        self.output().line(&best_move_line);
+    }
+
+    /// Returns the move expected to be played in reply to the best move. This is the second
+    /// move of the principal variation if there is one, otherwise it's found from the
+    /// transposition table.
+    fn ponder_move(&mut self, best_move: BitMove, pv_reply: Option<BitMove>) -> Option<BitMove> {
+        if best_move.is_null() {
+            return None;
+        }
+        if let Some(reply) = pv_reply {
+            return Some(reply);
+        }
+        self.board.apply_move(best_move);
+        let (tt_hit, tt_entry) = self.tt().probe(self.board.zobrist());
+        let ponder_move = if tt_hit && self.board.generate_moves().contains(&tt_entry.best_move) {
+            Some(tt_entry.best_move)
+        } else {
+            None
+        };
+        self.board.undo_move();
+        ponder_move
     }
 
     // The per thread searching function
@@ -299,8 +424,8 @@ impl Searcher {
         }
 
         // notify GUI that this thread is starting
-        if self.use_stdout() {
-            println!("info id {} start", self.id);
+        if self.has_output() {
+            self.output().line(&format!("info id {} start", self.id));
         }
 
         let mut stack: ThreadStack = ThreadStack::new();
@@ -320,11 +445,30 @@ impl Searcher {
             MAX_PLY as i16
         };
 
+        // At a reduced skill level, there is no need to search past the depth the move is
+        // picked at, unless searching until told to stop.
+        let max_depth = if self.main_thread() && self.skill.enabled() && !self.limit.infinite {
+            max_depth.min(self.skill.max_depth() + 1)
+        } else {
+            max_depth
+        };
+
         if self.main_thread() {
             self.best_move_changes = 0.0;
             self.failed_low = false;
         }
 
+        // Contempt is relative to the side to move at the root, which is the side we play.
+        let us = self.board.turn();
+        let ct = self.limit.contempt * PAWN_EG / 100;
+        self.contempt = if us == Player::White {
+            Score(ct, ct / 2)
+        } else {
+            -Score(ct, ct / 2)
+        };
+        self.draw_value[us as usize] = DRAW - ct;
+        self.draw_value[!us as usize] = DRAW + ct;
+
         // The depth to start searching at based on the thread ID.
         let start_ply: i16 = START_PLY[self.id % THREAD_DIST];
         // The number of plies to skip each iteration.
@@ -353,61 +497,76 @@ impl Searcher {
             // rollback all the root moves, ala set the previous score to the current score.
             self.root_moves().rollback();
 
-            // Delta gives a bound in the iterative loop before re-searching that position.
-            // Only applicable for a depth of 5 and beyond.
-            if depth >= 5 {
-                let prev_best_score = self.root_moves().first().prev_score;
-                delta = 20;
-                alpha = max(prev_best_score - delta, NEG_INFINITE);
-                beta = min(prev_best_score + delta, INFINITE);
-            }
-
-            // Loop until we find a value that is within the bounds of alpha, beta, and the delta margin.
-            'aspiration_window: loop {
-                // search!
-                best_value = self.search::<PV>(alpha, beta, stack.ply_zero(), depth, false, false);
-
-                // Sort root moves based on the scores
-                self.root_moves().sort();
-
-                if self.stop() {
-                    // In case of a fail high or fail low, we do not choose to sort the moves,
-                    // as the resulting scores would be incorrect
-                    break 'aspiration_window;
+            // The number of principal variations to search, one after another.
+            let multi_pv: usize = self.limit.multi_pv.min(self.root_moves().len());
+
+            // Search each PV line, excluding the moves of the previous lines from the root.
+            self.pv_idx = 0;
+            while self.pv_idx < multi_pv && !self.stop() {
+                let pv_idx = self.pv_idx;
+                self.selected_depth = 0;
+
+                // Delta gives a bound in the iterative loop before re-searching that position.
+                // Only applicable for a depth of 5 and beyond.
+                if depth >= 5 {
+                    let prev_best_score = self.root_moves()[pv_idx].prev_score;
+                    delta = 20;
+                    alpha = max(prev_best_score - delta, NEG_INFINITE);
+                    beta = min(prev_best_score + delta, INFINITE);
                 }
 
-                // Order root moves by the score retreived post search.
+                // Loop until we find a value that is within the bounds of alpha, beta, and the delta margin.
+                'aspiration_window: loop {
+                    // search!
+                    best_value =
+                        self.search::<PV>(alpha, beta, stack.ply_zero(), depth, false, false);
+
+                    // Sort the root moves of this PV line and the lines after it, based on the
+                    // scores.
+                    let len = self.root_moves().len();
+                    self.root_moves().sort_range(pv_idx, len);
+
+                    if self.stop() {
+                        // In case of a fail high or fail low, we do not choose to sort the moves,
+                        // as the resulting scores would be incorrect
+                        break 'aspiration_window;
+                    }
 
-                if self.use_stdout()
-                    && self.main_thread()
-                    && (best_value <= alpha || best_value >= beta)
-                    && self.time_man.elapsed() > 3000
-                {
-                    self.pv(depth, alpha, beta);
-                }
+                    if self.main_thread()
+                        && multi_pv == 1
+                        && (best_value <= alpha || best_value >= beta)
+                        && self.elapsed() > 3000
+                    {
+                        self.pv(depth, alpha, beta);
+                    }
 
-                // Check for incorrect search window. If the value if less than alpha
-                // or greater than beta, we need to increase the search window and re-search.
-                // Otherwise, go to the next search
-                if best_value <= alpha {
-                    beta = (alpha + beta) / 2;
-                    alpha = max(best_value - delta, NEG_INFINITE);
-                    if self.main_thread() {
-                        self.failed_low = true;
+                    // Check for incorrect search window. If the value if less than alpha
+                    // or greater than beta, we need to increase the search window and re-search.
+                    // Otherwise, go to the next search
+                    if best_value <= alpha {
+                        beta = (alpha + beta) / 2;
+                        alpha = max(best_value - delta, NEG_INFINITE);
+                        if self.main_thread() {
+                            self.failed_low = true;
+                        }
+                    } else if best_value >= beta {
+                        beta = min(best_value + delta, INFINITE);
+                    } else {
+                        break 'aspiration_window;
                     }
-                } else if best_value >= beta {
-                    beta = min(best_value + delta, INFINITE);
-                } else {
-                    break 'aspiration_window;
+                    delta += (delta / 4) + 5;
+
+                    assert!(alpha >= NEG_INFINITE);
+                    assert!(beta <= INFINITE);
                 }
-                delta += (delta / 4) + 5;
 
-                assert!(alpha >= NEG_INFINITE);
-                assert!(beta <= INFINITE);
+                // Sort the PV lines searched so far.
+                self.root_moves().sort_range(0, pv_idx + 1);
+                self.pv_idx += 1;
             }
 
             // Main Thread provides an update to the GUI
-            if self.use_stdout() && self.main_thread() && self.time_man.elapsed() > 6 {
+            if self.main_thread() {
                 if self.stop() {
                     self.pv(depth, NEG_INFINITE, INFINITE);
                 } else {
@@ -434,6 +593,24 @@ impl Searcher {
 
             // Main thread only from here on!
 
+            // Pick the move to play at a reduced skill level.
+            if self.skill.enabled() && !self.stop() && self.skill.time_to_pick(self.depth_completed)
+            {
+                let multi_pv = self.limit.multi_pv;
+                let root_moves = unsafe { &*self.root_moves.get() };
+                self.skill.pick_best(root_moves, multi_pv);
+            }
+
+            // Stop once a mate within the requested number of moves has been proven. With
+            // several PV lines, `best_value` is the score of the last line, so look at the
+            // first of the sorted root moves instead.
+            if let LimitsType::Mate(mate) = self.limit.limits_type {
+                let best_score = self.root_moves()[0].score;
+                if best_score >= MATE_IN_MAX_PLY && MATE - best_score <= 2 * mate as i32 {
+                    self.threadpool().set_stop(true);
+                }
+            }
+
             // check for time
             if let Some(_) = self.limit.use_time_management() {
                 if !self.stop() {
@@ -457,14 +634,22 @@ impl Searcher {
 
                     // Stop the search if we have only one legal move, or if available time elapsed
                     if self.root_moves().len() == 1
-                        || self.time_man.elapsed()
-                            >= (self.time_man.ideal_time() as f64
+                        || self.elapsed()
+                            >= (self.time_man().ideal_time() as f64
                                 * unstable_factor as f64
                                 * improving_factor as f64
                                 / 600.0) as i64
                     {
-                        threadpool().set_stop(true);
-                        break 'iterative_deepening;
+                        // If pondering, keep searching until the GUI tells us what the
+                        // opponent has played.
+                        if self.threadpool().ponder.load(Ordering::Relaxed) {
+                            self.threadpool()
+                                .stop_on_ponderhit
+                                .store(true, Ordering::Relaxed);
+                        } else {
+                            self.threadpool().set_stop(true);
+                            break 'iterative_deepening;
+                        }
                     }
                 }
             }
@@ -485,6 +670,19 @@ impl Searcher {
         cut_node: bool,
         skip_early_pruning: bool,
     ) -> i32 {
+        // Check if there is an upcoming move that draws by repetition, or if the opponent had
+        // an alternative move earlier that reaches this position.
+        if ss.ply > 0
+            && alpha < self.draw_value()
+            && self.board.rule_50() >= 3
+            && self.board.has_upcoming_repetition(ss.ply)
+        {
+            alpha = self.draw_value();
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
         if depth < 1 {
             return self.qsearch::<N>(alpha, beta, ss, 0);
         }
@@ -530,13 +728,18 @@ impl Searcher {
             self.check_time();
         }
 
+        // Keep track of the highest ply reached in a PV node.
+        if is_pv && self.selected_depth < ply as i16 + 1 {
+            self.selected_depth = ply as i16 + 1;
+        }
+
         if !at_root {
             // Check for stop conditions.
-            if self.stop() || ply >= MAX_PLY {
+            if self.stop() || self.board.is_draw(ply) || ply >= MAX_PLY {
                 if !in_check && ply >= MAX_PLY {
                     return self.eval();
                 } else {
-                    return ZERO;
+                    return self.draw_value();
                 }
             }
 
@@ -571,14 +774,14 @@ impl Searcher {
         // probe the transposition table
         excluded_move = ss.excluded_move;
         zob = self.board.zobrist() ^ (excluded_move.get_raw() as u64).wrapping_shl(16);
-        let (tt_hit, tt_entry): (bool, &mut Entry) = tt().probe(zob);
+        let (tt_hit, tt_entry): (bool, &mut Entry) = self.tt().probe(zob);
         let tt_value: Value = if tt_hit {
             value_from_tt(tt_entry.score, ss.ply)
         } else {
             NONE
         };
         let tt_move: BitMove = if at_root {
-            self.root_moves().first().bit_move
+            self.root_moves()[self.pv_idx].bit_move
         } else if tt_hit {
             tt_entry.best_move
         } else {
@@ -664,7 +867,7 @@ impl Searcher {
                     pos_eval as i16,
                     -6,
                     NodeBound::NoBound,
-                    tt().time_age(),
+                    self.tt().time_age(),
                 );
             }
 
@@ -733,9 +936,29 @@ impl Searcher {
                 continue;
             }
 
+            // At the root, skip moves that belong to the PV lines already searched.
+            if at_root
+                && !self
+                    .root_moves()
+                    .iter()
+                    .skip(self.pv_idx)
+                    .any(|rm| rm.bit_move == mov)
+            {
+                continue;
+            }
+
             moves_played += 1;
             ss.move_count = moves_played;
 
+            if at_root && self.has_output() && self.main_thread() && self.elapsed() > 3000 {
+                self.output().line(&format!(
+                    "info depth {} currmove {} currmovenumber {}",
+                    depth,
+                    self.board.uci_move(mov),
+                    moves_played as usize + self.pv_idx
+                ));
+            }
+
             extension = 0;
             gives_check = self.board.gives_check(mov);
             capture_or_promotion = self.board.is_capture_or_promotion(mov);
@@ -820,7 +1043,7 @@ impl Searcher {
             }
 
             // speculative prefetch for the next key.
-            tt().prefetch(self.board.key_after(mov));
+            self.tt().prefetch(self.board.key_after(mov));
 
             if !self.board.legal_move(mov) {
                 ss.move_count -= 1;
@@ -838,7 +1061,7 @@ impl Searcher {
             self.apply_move(mov, gives_check);
 
             // prefetch next TT entry
-            tt().prefetch(self.board.zobrist());
+            self.tt().prefetch(self.board.zobrist());
 
             // At higher depths, do a search of a lower ply to see if this move is
             // worth searching. We don't do this for capturing or promotion moves.
@@ -916,6 +1139,7 @@ impl Searcher {
             // If on the PV node and the node might be a continuation, search for a full depth
             // with a PV value.
             if is_pv && (moves_played == 1 || (value > alpha && (at_root || value < beta))) {
+                ss.incr().pv[0] = BitMove::null();
                 value = -self.search::<PV>(-beta, -alpha, ss.incr(), new_depth, false, false);
             }
 
@@ -937,7 +1161,9 @@ impl Searcher {
                     if moves_played == 1 || value > alpha {
                         rm.depth_reached = depth;
                         rm.score = value;
-                        if moves_played > 1 && self.main_thread() && depth > 5 {
+                        rm.sel_depth = self.selected_depth;
+                        rm.update_pv(&ss.incr().pv);
+                        if moves_played > 1 && self.main_thread() && depth > 5 && self.pv_idx == 0 {
                             incr_bmc = true;
                         }
                     } else {
@@ -957,7 +1183,7 @@ impl Searcher {
                     best_move = mov;
 
                     if is_pv && !at_root {
-                        ss.incr().pv = mov;
+                        ss.update_pv(mov);
                     }
 
                     if is_pv && value < beta {
@@ -987,7 +1213,7 @@ impl Searcher {
             } else if in_check {
                 return mated_in(ss.ply);
             } else {
-                return DRAW as i32;
+                return self.draw_value();
             }
         } else if best_move != BitMove::null() {
             // If the best move is quiet, update move heuristics
@@ -1046,7 +1272,7 @@ impl Searcher {
                 ss.static_eval as i16,
                 depth as i16,
                 node_bound,
-                tt().time_age(),
+                self.tt().time_age(),
             );
         }
 
@@ -1086,15 +1312,22 @@ impl Searcher {
 
         let in_check: bool = self.board.in_check();
 
-        if ply >= MAX_PLY {
-            if !in_check {
+        if is_pv {
+            ss.pv[0] = BitMove::null();
+            if self.selected_depth < ply as i16 + 1 {
+                self.selected_depth = ply as i16 + 1;
+            }
+        }
+
+        if self.board.is_draw(ply) || ply >= MAX_PLY {
+            if !in_check && ply >= MAX_PLY {
                 return self.eval();
             } else {
-                return ZERO;
+                return self.draw_value();
             }
         }
 
-        let (tt_hit, tt_entry): (bool, &mut Entry) = tt().probe(zob);
+        let (tt_hit, tt_entry): (bool, &mut Entry) = self.tt().probe(zob);
         let tt_value: Value = if tt_hit {
             value_from_tt(tt_entry.score, ss.ply)
         } else {
@@ -1158,7 +1391,7 @@ impl Searcher {
                         pos_eval as i16,
                         -6,
                         NodeBound::LowerBound,
-                        tt().time_age(),
+                        self.tt().time_age(),
                     );
                 }
                 return best_value;
@@ -1215,7 +1448,7 @@ impl Searcher {
                 continue;
             }
 
-            tt().prefetch(self.board.key_after(mov));
+            self.tt().prefetch(self.board.key_after(mov));
 
             if !self.board.legal_move(mov) {
                 moves_played -= 1;
@@ -1226,7 +1459,7 @@ impl Searcher {
             self.apply_move(mov, gives_check);
 
             // prefetch next TT entry
-            tt().prefetch(self.board.zobrist());
+            self.tt().prefetch(self.board.zobrist());
 
             assert_eq!(gives_check, self.board.in_check());
 
@@ -1243,7 +1476,7 @@ impl Searcher {
 
                 if value > alpha {
                     if is_pv {
-                        ss.incr().pv = best_move;
+                        ss.update_pv(mov);
                     }
                     if is_pv && value < beta {
                         best_move = mov;
@@ -1256,7 +1489,7 @@ impl Searcher {
                             ss.static_eval as i16,
                             tt_depth as i16,
                             NodeBound::LowerBound,
-                            tt().time_age(),
+                            self.tt().time_age(),
                         );
                         return value;
                     }
@@ -1282,7 +1515,7 @@ impl Searcher {
             ss.static_eval as i16,
             tt_depth,
             node_bound,
-            tt().time_age(),
+            self.tt().time_age(),
         );
 
         assert!(best_value > NEG_INFINITE);
@@ -1358,7 +1591,13 @@ impl Searcher {
     pub fn eval(&mut self) -> Value {
         let pawns = &mut self.pawns;
         let material = &mut self.material;
-        eval::Evaluation::evaluate(&self.board, pawns, material)
+        eval::Evaluation::evaluate(&self.board, pawns, material, self.contempt)
+    }
+
+    /// The score of a draw for the side to move.
+    #[inline(always)]
+    fn draw_value(&self) -> Value {
+        self.draw_value[self.board.turn() as usize]
     }
 
     #[inline(always)]
@@ -1368,31 +1607,84 @@ impl Searcher {
 
     #[inline(always)]
     fn stop(&self) -> bool {
-        threadpool().stop.load(Ordering::Relaxed)
+        self.threadpool().stop.load(Ordering::Relaxed)
     }
 
     fn check_time(&mut self) {
+        // There is no time limit while pondering.
+        if self.threadpool().ponder.load(Ordering::Relaxed) {
+            return;
+        }
+
         if self.limit.use_time_management().is_some()
-            && self.time_man.elapsed() >= self.time_man.maximum_time()
+            && self.elapsed() >= self.time_man().maximum_time()
         {
-            threadpool().set_stop(true);
+            self.threadpool().set_stop(true);
         } else if let Some(time) = self.limit.use_movetime() {
             if self.limit.elapsed() >= time as i64 {
-                threadpool().set_stop(true);
+                self.threadpool().set_stop(true);
+            }
+        } else if let Some(nodes) = self.limit.use_nodes() {
+            if self.threadpool().nodes() >= nodes {
+                self.threadpool().set_stop(true);
             }
         }
+
+        if self.skill.enabled()
+            && !self.limit.infinite
+            && self.threadpool().nodes() >= self.skill.max_nodes()
+        {
+            self.threadpool().set_stop(true);
+        }
     }
 
     #[inline(always)]
     pub fn print_startup(&self) {
-        if self.use_stdout() {
-            println!("info id {} start", self.id);
+        if self.has_output() {
+            self.output().line(&format!("info id {} start", self.id));
         }
     }
 
     #[inline(always)]
-    pub fn use_stdout(&self) -> bool {
-        USE_STDOUT.load(Ordering::Relaxed)
+    pub fn has_output(&self) -> bool {
+        self.output().is_enabled()
+    }
+
+    /// Returns where the search information is written.
+    #[inline(always)]
+    fn output<'a>(&self) -> &'a Output {
+        &self.threadpool().output
+    }
+
+    /// Returns the `ThreadPool` this searcher belongs to.
+    ///
+    /// The pool owns and outlives each of its searchers, so the reference isn't tied to
+    /// the borrow of the searcher.
+    #[inline(always)]
+    fn threadpool<'a>(&self) -> &'a ThreadPool {
+        unsafe { &*self.pool }
+    }
+
+    /// Returns the transposition table of the pool.
+    #[inline(always)]
+    fn tt<'a>(&self) -> &'a TranspositionTable {
+        &self.threadpool().tt
+    }
+
+    /// Returns the time manager of the pool.
+    #[inline(always)]
+    fn time_man<'a>(&self) -> &'a TimeManager {
+        &self.threadpool().timer
+    }
+
+    /// Returns the time elapsed since the start of the search in milliseconds, or the nodes
+    /// searched when using nodes as time.
+    fn elapsed(&self) -> i64 {
+        if self.time_man().nodes_as_time() {
+            self.threadpool().nodes() as i64
+        } else {
+            self.time_man().elapsed()
+        }
     }
 
     #[inline]
@@ -1400,49 +1692,84 @@ impl Searcher {
         unsafe { &mut *self.root_moves.get() }
     }
 
-    /// Useful information to tell to the GUI
+    /// Useful information to tell to the GUI. Prints a line for each of the principal
+    /// variations being searched, and passes them on to the `InfoSink` of the pool.
     fn pv(&self, depth: i16, alpha: i32, beta: i32) {
-        let root_move: &RootMove = self.root_moves().first();
-        let elapsed = self.time_man.elapsed() as u64;
-        let nodes = threadpool().nodes();
-        let mut score = if root_move.score == NEG_INFINITE {
-            root_move.prev_score
-        } else {
-            root_move.score
-        };
-
-        if score == NEG_INFINITE {
+        let sink = self.threadpool().info_sink.as_ref();
+        if !self.has_output() && sink.is_none() {
             return;
         }
+        let info = self.search_info(depth, alpha, beta);
+        if info.lines.is_empty() {
+            return;
+        }
+        // Very short searches aren't worth printing.
+        if self.has_output() && info.time > 6 {
+            self.output().line(&info.uci_lines(&self.board));
+        }
+        if let Some(sink) = sink {
+            sink.info(&info);
+        }
+    }
 
-        let mut s = String::from("info");
-        s.push_str(&format!(" depth {}", depth));
-        if score.abs() < MATE - MAX_PLY as i32 {
-            score *= 100;
-            score /= PAWN_EG;
-            s.push_str(&format!(" score cp {}", score));
-        } else {
-            let mut mate_in = if score > 0 {
-                MATE - score + 1
+    /// Collects the principal variations being searched.
+    fn search_info(&self, depth: i16, alpha: i32, beta: i32) -> SearchInfo {
+        let elapsed = self.elapsed() as u64;
+        let nodes = self.threadpool().nodes();
+        let multi_pv: usize = self.limit.multi_pv.min(self.root_moves().len());
+        let mut lines = Vec::with_capacity(multi_pv);
+
+        for i in 0..multi_pv {
+            let root_move: &RootMove = &self.root_moves()[i];
+            // Lines after the current PV line have only been searched in the previous iteration.
+            let updated: bool = i <= self.pv_idx && root_move.score != NEG_INFINITE;
+            if depth == 1 && !updated {
+                continue;
+            }
+
+            let line_depth = if updated { depth } else { (depth - 1).max(1) };
+            let score = if updated {
+                root_move.score
             } else {
-                -MATE - score
+                root_move.prev_score
             };
-            mate_in /= 2;
-            s.push_str(&format!(" score mate {}", mate_in));
-        }
-        if root_move.score >= beta {
-            s.push_str(" lowerbound");
-        } else if root_move.score <= alpha {
-            s.push_str(" upperbound");
+
+            if score == NEG_INFINITE {
+                continue;
+            }
+
+            let bound = if i != self.pv_idx {
+                ScoreBound::Exact
+            } else if root_move.score >= beta {
+                ScoreBound::Lower
+            } else if root_move.score <= alpha {
+                ScoreBound::Upper
+            } else {
+                ScoreBound::Exact
+            };
+            let wdl = if self.limit.show_wdl {
+                Some(wdl::wdl(score, self.board.moves_played()))
+            } else {
+                None
+            };
+            lines.push(PvLine {
+                multi_pv: i + 1,
+                depth: line_depth,
+                sel_depth: root_move.sel_depth.max(line_depth),
+                score: SearchScore::from_value(score),
+                bound,
+                wdl,
+                pv: root_move.pv().to_vec(),
+            });
         }
-        s.push_str(&format!(" nodes {}", nodes));
-        if elapsed > 1000 {
-            s.push_str(&format!(" nps {}", (nodes * 1000) / elapsed));
-            s.push_str(&format!(" hashfull {:.2}", tt().hash_percent()));
+
+        SearchInfo {
+            nodes,
+            nps: (nodes * 1000).checked_div(elapsed).unwrap_or(0),
+            hash_full: (self.tt().hash_percent() * 10.0) as u32,
+            time: elapsed,
+            lines,
         }
-        s.push_str(&format!(" time {}", elapsed));
-        s.push_str(&format!(" pv {}", root_move.bit_move.to_string()));
-        println!("{}", s);
     }
 }
 
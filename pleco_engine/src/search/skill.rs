@@ -0,0 +1,121 @@
+//! Weakening of the searcher through a skill level, for playing against humans.
+//!
+//! At a reduced skill level, the searcher looks at several principal variations at a limited
+//! depth and node count. Once done, it picks one of the candidate moves at random, favoring
+//! moves that scored close to the best one. The lower the level, the more likely it is for a
+//! weaker move to be chosen.
+
+use rand;
+
+use pleco::core::score::*;
+use pleco::BitMove;
+
+use consts::{MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO};
+use root_moves::root_moves_list::RootMoveList;
+
+/// Minimum number of principal variations searched when the skill level is reduced, so there
+/// are always a few alternatives to the best move.
+pub const SKILL_MULTI_PV: usize = 4;
+
+//                                               0    1    2    3     4     5     6     7     8
+static SKILL_NODES: [u64; MAX_SKILL_LEVEL as usize] = [
+    250, 330, 450, 650, 1_100, 1_800, 2_800, 4_200, 6_100, 8_600, 12_000, 17_000, 23_000, 30_000,
+    40_000, 53_000, 69_000, 90_000, 115_000, 147_000,
+];
+
+/// Converts a UCI_Elo rating into a skill level.
+///
+/// Ratings are mapped onto levels with the power curve Stockfish uses for its own levels. It
+/// hasn't been calibrated for this engine, so the rating played at is only a rough guide.
+pub fn level_from_elo(elo: u16) -> u8 {
+    let elo = (elo as i32).max(MIN_ELO).min(MAX_ELO) as f64;
+    let level = ((elo - 1346.6) / 143.4).powf(1.0 / 0.806);
+    (level as u8).min(MAX_SKILL_LEVEL - 1)
+}
+
+pub struct Skill {
+    level: u8,
+    best: BitMove,
+}
+
+impl Skill {
+    pub fn new(level: u8) -> Self {
+        Skill {
+            level: level.min(MAX_SKILL_LEVEL),
+            best: BitMove::null(),
+        }
+    }
+
+    /// Returns if the searcher is playing below full strength.
+    #[inline]
+    pub fn enabled(&self) -> bool {
+        self.level < MAX_SKILL_LEVEL
+    }
+
+    /// The deepest iteration searched at this skill level. The move is picked after it.
+    ///
+    /// Like the node limit, this grows with the level by hand, and isn't calibrated to a rating.
+    #[inline]
+    pub fn max_depth(&self) -> i16 {
+        1 + self.level as i16
+    }
+
+    /// The maximum number of nodes searched at this skill level.
+    #[inline]
+    pub fn max_nodes(&self) -> u64 {
+        SKILL_NODES[self.level as usize]
+    }
+
+    /// Returns if the move should be picked after an iteration of `depth`.
+    #[inline]
+    pub fn time_to_pick(&self, depth: i16) -> bool {
+        depth == self.max_depth()
+    }
+
+    /// Returns the picked move, picking one first if that hasn't happened yet.
+    pub fn best_move(&mut self, root_moves: &RootMoveList, multi_pv: usize) -> BitMove {
+        if self.best.is_null() {
+            self.pick_best(root_moves, multi_pv)
+        } else {
+            self.best
+        }
+    }
+
+    /// Chooses a move from the first `multi_pv` root moves. Each move gets a random bonus,
+    /// which grows with the weakness and how close the principal variations are.
+    pub fn pick_best(&mut self, root_moves: &RootMoveList, multi_pv: usize) -> BitMove {
+        let multi_pv = multi_pv.min(root_moves.len());
+        let top_score: i32 = root_moves[0].score;
+        let delta: i32 = (top_score - root_moves[multi_pv - 1].score).min(PAWN_MG);
+        let weakness: i32 = 120 - 2 * self.level as i32;
+        let mut max_score: i32 = NEG_INFINITE;
+
+        for rm in root_moves.iter().take(multi_pv) {
+            let random = (rand::random::<u32>() % weakness as u32) as i32;
+            let push = (weakness * (top_score - rm.score) + delta * random) / 128;
+            if rm.score + push >= max_score {
+                max_score = rm.score + push;
+                self.best = rm.bit_move;
+            }
+        }
+        self.best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elo_levels() {
+        assert_eq!(level_from_elo(MIN_ELO as u16), 0);
+        assert_eq!(level_from_elo(0), 0);
+        assert!(level_from_elo(MAX_ELO as u16) < MAX_SKILL_LEVEL);
+        let mut prev = 0;
+        for elo in MIN_ELO..MAX_ELO {
+            let level = level_from_elo(elo as u16);
+            assert!(level >= prev);
+            prev = level;
+        }
+    }
+}
@@ -85,6 +85,12 @@ impl GuardedBool {
         self.a.set_value(value);
     }
 
+    /// Returns the current value.
+    #[inline]
+    pub fn get(&self) -> bool {
+        *self.a.m.lock().unwrap()
+    }
+
     /// Awaits a value.
     #[inline]
     pub fn await(&self, value: bool) {
@@ -4,61 +4,26 @@ use std::alloc::{handle_alloc_error, Alloc, Global, Layout};
 use std::cell::UnsafeCell;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Once, ONCE_INIT};
 use std::thread::{self, JoinHandle};
 use std::{mem, ptr};
 
 use pleco::board::*;
 use pleco::core::piece_move::BitMove;
 use pleco::tools::pleco_arc::Arc;
+use pleco::tools::tt::TranspositionTable;
 use pleco::MoveList;
 
+use search::info::{InfoSink, SearchResult};
 use search::Searcher;
 use sync::LockLatch;
+use time::time_management::TimeManager;
 use time::uci_timer::*;
+use uci::output::Output;
 
 use consts::*;
 
 const KILOBYTE: usize = 1000;
 const THREAD_STACK_SIZE: usize = 18000 * KILOBYTE;
-const POOL_SIZE: usize = mem::size_of::<ThreadPool>();
-
-// An object that is the same size as a thread pool.
-type DummyThreadPool = [u8; POOL_SIZE];
-
-// The Global threadpool! Yes, this is *technically* an array the same
-// size as a ThreadPool object. This is a cheap hack to get a global value, as
-// Rust isn't particularily fond of mutable global statics.
-pub static mut THREADPOOL: DummyThreadPool = [0; POOL_SIZE];
-
-// ONCE for the Threadpool
-static THREADPOOL_INIT: Once = ONCE_INIT;
-
-// Initializes the threadpool, called once on startup.
-#[cold]
-pub fn init_threadpool() {
-    THREADPOOL_INIT.call_once(|| {
-        unsafe {
-            // We have a spawned thread create all structures, as a stack overflow can
-            // occur otherwise
-            let builder = thread::Builder::new()
-                .name("Starter".to_string())
-                .stack_size(THREAD_STACK_SIZE);
-
-            let handle = builder.spawn_unchecked(move || {
-                let pool: *mut ThreadPool = mem::transmute(&mut THREADPOOL);
-                ptr::write(pool, ThreadPool::new());
-            });
-            handle.unwrap().join().unwrap();
-        }
-    });
-}
-
-/// Returns access to the global thread pool.
-#[inline(always)]
-pub fn threadpool() -> &'static mut ThreadPool {
-    unsafe { mem::transmute::<&mut DummyThreadPool, &'static mut ThreadPool>(&mut THREADPOOL) }
-}
 
 // Dummy struct to allow us to pass a pointer into a spawned thread.
 struct SearcherPtr {
@@ -68,7 +33,8 @@ struct SearcherPtr {
 unsafe impl Sync for SearcherPtr {}
 unsafe impl Send for SearcherPtr {}
 
-/// The thread-pool for the chess engine.
+/// The thread-pool for the chess engine. Each pool has its own threads, transposition table
+/// and time manager, so several pools can search independently of each other.
 pub struct ThreadPool {
     /// Access to each thread's Structure
     pub threads: Vec<UnsafeCell<*mut Searcher>>,
@@ -80,6 +46,21 @@ pub struct ThreadPool {
     pub thread_cond: Arc<LockLatch>,
     /// Stop condition, if true the threads should halt.
     pub stop: AtomicBool,
+    /// Set when the search is stopped or the pondering is over, waking up a main thread
+    /// that is holding on to its best move.
+    pub stop_cond: LockLatch,
+    /// If true, the search is pondering on the opponent's time.
+    pub ponder: AtomicBool,
+    /// If true, the search should stop as soon as the pondering is over.
+    pub stop_on_ponderhit: AtomicBool,
+    /// Where the search information and best move are written.
+    pub output: Output,
+    /// The transposition table shared by the threads.
+    pub tt: TranspositionTable,
+    /// Manages the time of a search.
+    pub timer: TimeManager,
+    /// Receives the information of each iteration of a search.
+    pub info_sink: Option<Box<InfoSink>>,
 }
 
 // Okay, this all looks like madness, but there is some reason to it all.
@@ -92,15 +73,25 @@ pub struct ThreadPool {
 // The goal of the ThreadPool is to be NON BLOCKING, unless we want to await a
 // result.
 impl ThreadPool {
-    /// Creates a new `ThreadPool`
-    pub fn new() -> Self {
-        let mut pool: ThreadPool = ThreadPool {
+    /// Creates a new `ThreadPool` with a single thread and a transposition table of
+    /// `tt_size` megabytes.
+    ///
+    /// The pool is boxed, as each thread keeps a pointer to it.
+    pub fn new(tt_size: usize) -> Box<Self> {
+        let mut pool: Box<ThreadPool> = Box::new(ThreadPool {
             threads: Vec::new(),
             handles: Vec::new(),
             main_cond: Arc::new(LockLatch::new()),
             thread_cond: Arc::new(LockLatch::new()),
             stop: AtomicBool::new(true),
-        };
+            stop_cond: LockLatch::new(),
+            ponder: AtomicBool::new(false),
+            stop_on_ponderhit: AtomicBool::new(false),
+            output: Output::stdout(),
+            tt: TranspositionTable::new(tt_size),
+            timer: TimeManager::uninitialized(),
+            info_sink: None,
+        });
         // Lock both the cond variables
         pool.main_cond.lock();
         pool.thread_cond.lock();
@@ -127,6 +118,9 @@ impl ThreadPool {
                 .unwrap();
             self.handles.push(handle);
         };
+        // Wait for the thread to reach its idle loop, otherwise a search started right away
+        // could be marked as finished by the thread starting up.
+        self.wait_for_finish();
     }
 
     /// Allocates a thread structure and pushes it to the threadstack.
@@ -148,7 +142,7 @@ impl ThreadPool {
                 Ok(ptr) => ptr.cast().as_ptr() as *mut Searcher,
                 Err(_err) => handle_alloc_error(layout),
             };
-            ptr::write(new_ptr, Searcher::new(len, cond));
+            ptr::write(new_ptr, Searcher::new(len, cond, self));
             self.threads.push(UnsafeCell::new(new_ptr));
             SearcherPtr {
                 ptr: UnsafeCell::new(new_ptr),
@@ -170,10 +164,21 @@ impl ThreadPool {
         }
     }
 
-    /// Sets the use of standard out. This can be changed mid search as well.
-    #[inline(always)]
+    /// Sets the use of standard out. Any running search is waited on first.
     pub fn stdout(&mut self, use_stdout: bool) {
-        USE_STDOUT.store(use_stdout, Ordering::Relaxed);
+        let output = if use_stdout {
+            Output::stdout()
+        } else {
+            Output::none()
+        };
+        self.set_output(output);
+    }
+
+    /// Sets where the search information and best move are written. Any running search is
+    /// waited on first.
+    pub fn set_output(&mut self, output: Output) {
+        self.wait_for_finish();
+        self.output = output;
     }
 
     /// Sets the thread count of the pool. If num is less than 1, nothing will happen.
@@ -195,7 +200,7 @@ impl ThreadPool {
     /// Kills and de-allocates all the threads that are running. This function will also
     /// block on waiting for the search to finish.
     pub fn kill_all(&mut self) {
-        self.stop.store(true, Ordering::Relaxed);
+        self.set_stop(true);
         self.wait_for_finish();
         let mut join_handles = Vec::with_capacity(self.size());
         unsafe {
@@ -236,8 +241,27 @@ impl ThreadPool {
 
     /// Sets the threads to stop (or not!).
     #[inline(always)]
-    pub fn set_stop(&mut self, stop: bool) {
+    pub fn set_stop(&self, stop: bool) {
         self.stop.store(stop, Ordering::Relaxed);
+        if stop {
+            self.stop_cond.set();
+        }
+    }
+
+    /// Switches a pondering search to a normal search, as the opponent has played the
+    /// expected move. If the search would have already stopped, it is stopped now.
+    pub fn ponderhit(&self) {
+        self.ponder.store(false, Ordering::Relaxed);
+        if self.stop_on_ponderhit.load(Ordering::Relaxed) {
+            self.set_stop(true);
+        } else {
+            self.stop_cond.set();
+        }
+    }
+
+    /// Returns if a search is running. The main thread is the last to finish searching.
+    pub fn is_searching(&self) -> bool {
+        unsafe { (**self.threads.get_unchecked(0).get()).searching.get() }
     }
 
     /// Waits for all the threads to finish
@@ -274,20 +298,6 @@ impl ThreadPool {
         }
     }
 
-    /// Waits for all the non-main threads to start running
-    pub fn wait_for_main_start(&self) {
-        unsafe {
-            self.threads
-                .iter()
-                .map(|s| &**s.get())
-                .for_each(|t: &Searcher| {
-                    if t.id == 0 {
-                        t.searching.await(true);
-                    }
-                });
-        }
-    }
-
     pub fn clear_all(&mut self) {
         for thread_ptr in self.threads.iter_mut() {
             let mut thread: &mut Searcher = unsafe { &mut **(*thread_ptr).get() };
@@ -297,19 +307,51 @@ impl ThreadPool {
 
     /// Starts a UCI search. The result will be printed to stdout if the stdout setting
     /// is true.
+    ///
+    /// If `limits.search_moves` is not empty, only the moves inside of it are searched. They
+    /// have to be legal moves of the board.
     pub fn uci_search(&mut self, board: &Board, limits: &Limits) {
         // Start the timer!
         if let Some(uci_timer) = limits.use_time_management() {
-            timer().init(limits.start, &uci_timer, board.turn(), board.moves_played());
+            self.timer
+                .init(limits, &uci_timer, board.turn(), board.moves_played());
         } else {
-            timer().start_timer(limits.start);
+            self.timer.start_timer(limits.start);
         }
 
-        let root_moves: MoveList = board.generate_moves();
+        let legal_moves: MoveList = board.generate_moves();
 
-        assert!(!root_moves.is_empty());
         self.wait_for_finish();
+
+        // Without any legal moves, the game is already over and there is nothing to search.
+        if legal_moves.is_empty() {
+            for thread_ptr in self.threads.iter_mut() {
+                let thread: &mut Searcher = unsafe { &mut **(*thread_ptr).get() };
+                thread.root_moves().replace(&legal_moves);
+            }
+            let result = SearchResult::game_over(board.in_check());
+            if let Some(ref sink) = self.info_sink {
+                sink.result(&result);
+            }
+            self.main().result = result;
+            if board.in_check() {
+                self.output.line("info depth 0 score mate 0");
+            } else {
+                self.output.line("info depth 0 score cp 0");
+            }
+            self.output.line("bestmove (none)");
+            return;
+        }
+
+        let root_moves: MoveList = if limits.search_moves.is_empty() {
+            legal_moves
+        } else {
+            limits.search_moves.iter().cloned().collect()
+        };
+
         self.stop.store(false, Ordering::Relaxed);
+        self.ponder.store(limits.ponder, Ordering::Relaxed);
+        self.stop_on_ponderhit.store(false, Ordering::Relaxed);
 
         for thread_ptr in self.threads.iter_mut() {
             let mut thread: &mut Searcher = unsafe { &mut **(*thread_ptr).get() };
@@ -320,9 +362,10 @@ impl ThreadPool {
             thread.root_moves().replace(&root_moves);
         }
 
+        // Mark the main thread as searching before waking it up, so a search that finishes
+        // immediately can't be missed by `wait_for_finish`.
+        self.main().searching.set(true);
         self.main_cond.set();
-        self.wait_for_main_start();
-        self.main_cond.lock();
     }
 
     /// Performs a standard search, and blocks waiting for a returned `BitMove`.
@@ -332,9 +375,17 @@ impl ThreadPool {
         self.best_move()
     }
 
-    /// Returns the best move of a search
+    /// Returns the best move of a search, or a null move if there were no legal moves to search.
     pub fn best_move(&mut self) -> BitMove {
-        self.main().root_moves().get(0).unwrap().bit_move
+        self.main()
+            .root_moves()
+            .get(0)
+            .map_or(BitMove::null(), |rm| rm.bit_move)
+    }
+
+    /// Returns the result of the last search.
+    pub fn search_result(&mut self) -> SearchResult {
+        self.main().result.clone()
     }
 
     /// Returns total number of nodes searched so far.
@@ -2,7 +2,7 @@
 
 use chrono;
 
-use super::uci_timer::UCITimer;
+use super::uci_timer::{Limits, UCITimer};
 use pleco::Player;
 
 use std::cell::UnsafeCell;
@@ -13,12 +13,15 @@ const MOVE_HORIZON: i64 = 50;
 const MAX_RATIO: f64 = 6.32;
 const STEAL_RATIO: f64 = 0.34;
 
-// TODO: These should be made into UCIOptions
 const MIN_THINKING_TIME: i64 = 20;
-const MOVE_OVERHEAD: i64 = 100;
 
-// Lower values means places less importance on the current move
-const SLOW_MOVER: i64 = 22;
+/// The default time in milliseconds kept in reserve for each move, to account for the latency
+/// of the GUI or network.
+pub const DEFAULT_MOVE_OVERHEAD: i64 = 100;
+
+/// The default importance of the current move. Lower values places less importance on the
+/// current move, and thus uses less time for it.
+pub const DEFAULT_SLOW_MOVER: i64 = 22;
 
 #[derive(PartialEq)]
 enum TimeCalc {
@@ -48,6 +51,9 @@ pub struct TimeManager {
     ideal_time: UnsafeCell<i64>,
     maximum_time: UnsafeCell<i64>,
     start: UnsafeCell<Instant>,
+    nodes_time: UnsafeCell<i64>, // nodes searched per millisecond, or 0 to use the clock
+    available_nodes: UnsafeCell<i64>, // nodes left on the clock when using nodes as time
+    nodes_inc: UnsafeCell<i64>,  // nodes gained after each move when using nodes as time
 }
 
 unsafe impl Sync for TimeManager {}
@@ -58,6 +64,16 @@ impl TimeManager {
             ideal_time: UnsafeCell::new(0),
             maximum_time: UnsafeCell::new(0),
             start: UnsafeCell::new(Instant::now()),
+            nodes_time: UnsafeCell::new(0),
+            available_nodes: UnsafeCell::new(0),
+            nodes_inc: UnsafeCell::new(0),
+        }
+    }
+
+    /// Resets the nodes available when using nodes as time, for the start of a new game.
+    pub fn clear(&self) {
+        unsafe {
+            *self.available_nodes.get() = 0;
         }
     }
 
@@ -65,15 +81,36 @@ impl TimeManager {
         unsafe {
             let self_start = self.start.get();
             *self_start = start;
+            *self.nodes_time.get() = 0;
         }
     }
 
-    pub fn init(&self, start: Instant, timer: &UCITimer, turn: Player, ply: u16) {
+    /// Calculates the ideal and maximum time to search for, given the time control of `limits`.
+    pub fn init(&self, limits: &Limits, timer: &UCITimer, turn: Player, ply: u16) {
         let moves_to_go: i64 = timer.moves_to_go as i64;
-        let my_time: i64 = (timer.time_msec[turn as usize]) as i64;
-        let my_inc: i64 = (timer.inc_msec[turn as usize]) as i64;
+        let mut my_time: i64 = timer.time_msec[turn as usize].max(0);
+        let mut my_inc: i64 = timer.inc_msec[turn as usize].max(0);
+        let nodes_time: i64 = limits.nodes_time;
+
+        // With nothing on the clock but the increment, the increment is all there is to use.
+        if my_time == 0 {
+            my_time = my_inc;
+        }
+
+        // When using nodes as time, the nodes available are set at the start of the game from
+        // the clock, and then only change by the nodes searched and the increment.
+        if nodes_time != 0 {
+            unsafe {
+                let available_nodes = self.available_nodes.get();
+                if *available_nodes == 0 {
+                    *available_nodes = nodes_time * my_time;
+                }
+                my_time = *available_nodes;
+            }
+            my_inc *= nodes_time;
+        }
 
-        let mut ideal_time = (timer.time_msec[turn as usize]).max(MIN_THINKING_TIME);
+        let mut ideal_time = my_time.max(MIN_THINKING_TIME);
         let mut max_time = ideal_time;
 
         let max_mtg: i64 = if moves_to_go == 0 {
@@ -87,7 +124,7 @@ impl TimeManager {
         // hypMTG gives the minimum values.
         for hyp_mtg in 1..(max_mtg + 1) {
             let mut hyp_my_time: i64 =
-                my_time + my_inc * (hyp_mtg - 1) - MOVE_OVERHEAD * (2 + hyp_mtg.min(40));
+                my_time + my_inc * (hyp_mtg - 1) - limits.move_overhead * (2 + hyp_mtg.min(40));
             hyp_my_time = hyp_my_time.max(0);
 
             let t1: i64 = MIN_THINKING_TIME
@@ -95,7 +132,7 @@ impl TimeManager {
                     hyp_my_time,
                     hyp_mtg,
                     ply as i64,
-                    SLOW_MOVER,
+                    limits.slow_mover,
                     TimeCalc::Ideal,
                 );
             let t2: i64 = MIN_THINKING_TIME
@@ -103,7 +140,7 @@ impl TimeManager {
                     hyp_my_time,
                     hyp_mtg,
                     ply as i64,
-                    SLOW_MOVER - 5,
+                    limits.slow_mover - 5,
                     TimeCalc::Max,
                 );
 
@@ -111,13 +148,35 @@ impl TimeManager {
             max_time = t2.min(max_time);
         }
 
+        // Pondering makes up for some of the time spent, so more of it can be used.
+        if limits.use_ponder {
+            ideal_time += ideal_time / 4;
+        }
+
+        // Never plan on using the whole clock, such as for the last move before the time
+        // control, as the search can overshoot the time it was given.
+        max_time = max_time.min(my_time * 4 / 5 - limits.move_overhead);
+        max_time = max_time.max(MIN_THINKING_TIME);
+        ideal_time = ideal_time.min(max_time);
+
         unsafe {
             let self_start = self.start.get();
             let self_ideal = self.ideal_time.get();
             let self_max = self.maximum_time.get();
-            *self_start = start;
+            *self_start = limits.start;
             *self_ideal = ideal_time;
             *self_max = max_time;
+            *self.nodes_time.get() = nodes_time;
+            *self.nodes_inc.get() = my_inc;
+        }
+    }
+
+    /// Updates the nodes available after a search when using nodes as time.
+    pub fn spend_nodes(&self, nodes: u64) {
+        unsafe {
+            if *self.nodes_time.get() != 0 {
+                *self.available_nodes.get() += *self.nodes_inc.get() - nodes as i64;
+            }
         }
     }
 
@@ -128,6 +187,13 @@ impl TimeManager {
         }
     }
 
+    /// Returns if the nodes searched are used in place of the time elapsed, as set by
+    /// the `nodestime` option.
+    pub fn nodes_as_time(&self) -> bool {
+        unsafe { *self.nodes_time.get() != 0 }
+    }
+
+    /// Returns the time elapsed since the start of the search in milliseconds.
     pub fn elapsed(&self) -> i64 {
         let start = self.start();
         chrono::Duration::from_std(start.elapsed())
@@ -186,18 +252,82 @@ impl TimeManager {
 mod tests {
     use super::*;
 
+    fn timer(time: i64, inc: i64, moves_to_go: u32) -> UCITimer {
+        UCITimer {
+            time_msec: [time, 0],
+            inc_msec: [inc, 0],
+            moves_to_go,
+        }
+    }
+
     #[test]
     fn time_man() {
-        let timer = UCITimer {
-            time_msec: [120000, 0],
-            inc_msec: [6000, 0],
-            moves_to_go: 20,
-        };
-        let ply: u16 = 0;
+        let limits = Limits::blank();
         let time_man = TimeManager::uninitialized();
-        time_man.init(Instant::now(), &timer, Player::White, ply);
+        time_man.init(&limits, &timer(120000, 6000, 20), Player::White, 0);
         let max = time_man.maximum_time();
         let ideal = time_man.ideal_time();
-        println!("ideal: {} max: {}", ideal, max);
+        assert!(ideal <= max);
+        assert!(max < 120000);
+
+        // A short clock still leaves time to search.
+        time_man.init(&limits, &timer(6000, 0, 0), Player::White, 20);
+        assert!(time_man.ideal_time() > MIN_THINKING_TIME);
+        assert!(time_man.maximum_time() < 6000 - limits.move_overhead);
+
+        // Even shorter clocks need a lower move overhead to leave time to search.
+        let mut bullet = Limits::blank();
+        bullet.move_overhead = 30;
+        time_man.init(&bullet, &timer(3000, 0, 0), Player::White, 20);
+        assert!(time_man.ideal_time() > MIN_THINKING_TIME);
+        assert!(time_man.maximum_time() < 3000 - bullet.move_overhead);
+
+        // Only an increment.
+        time_man.init(&limits, &timer(0, 1000, 0), Player::White, 20);
+        assert!(time_man.ideal_time() > MIN_THINKING_TIME);
+        assert!(time_man.maximum_time() <= 1000);
+
+        // The last move before the time control may use most of the clock.
+        time_man.init(&limits, &timer(10000, 0, 1), Player::White, 20);
+        let last_move = time_man.ideal_time();
+        time_man.init(&limits, &timer(10000, 0, 10), Player::White, 20);
+        assert!(last_move > time_man.ideal_time());
+        assert!(last_move <= 8000);
+
+        // A larger move overhead leaves less time.
+        let mut slow_network = Limits::blank();
+        slow_network.move_overhead = 500;
+        time_man.init(&slow_network, &timer(10000, 0, 0), Player::White, 20);
+        let overhead_time = time_man.ideal_time();
+        time_man.init(&limits, &timer(10000, 0, 0), Player::White, 20);
+        assert!(overhead_time < time_man.ideal_time());
+
+        // With pondering on, a quarter more time is planned for.
+        let mut ponder = Limits::blank();
+        ponder.use_ponder = true;
+        time_man.init(&ponder, &timer(60000, 0, 30), Player::White, 20);
+        let ponder_time = time_man.ideal_time();
+        time_man.init(&limits, &timer(60000, 0, 30), Player::White, 20);
+        let ideal = time_man.ideal_time();
+        assert_eq!(ponder_time, ideal + ideal / 4);
+    }
+
+    #[test]
+    fn nodes_as_time() {
+        let mut limits = Limits::blank();
+        limits.nodes_time = 1000;
+        let time_man = TimeManager::uninitialized();
+        time_man.init(&limits, &timer(10000, 100, 0), Player::White, 20);
+        let ideal = time_man.ideal_time();
+        assert!(ideal > 10000);
+
+        // Searching more nodes than the increment leaves fewer nodes for the next move.
+        time_man.spend_nodes(1_000_000);
+        time_man.init(&limits, &timer(10000, 100, 0), Player::White, 22);
+        assert!(time_man.ideal_time() < ideal);
+
+        time_man.clear();
+        time_man.init(&limits, &timer(10000, 100, 0), Player::White, 20);
+        assert_eq!(time_man.ideal_time(), ideal);
     }
 }
@@ -1,6 +1,9 @@
 use chrono;
+use consts::MAX_SKILL_LEVEL;
 use pleco::core::masks::PLAYER_CNT;
+use pleco::BitMove;
 use std::time;
+use time::time_management::{DEFAULT_MOVE_OVERHEAD, DEFAULT_SLOW_MOVER};
 
 #[derive(Clone)]
 pub enum LimitsType {
@@ -10,7 +13,6 @@ pub enum LimitsType {
     Depth(u16),     // Search only x plys
     Nodes(u64),     // Search only x nodes
     Infinite,       // infinite
-    Ponder,         // ponder mode
 }
 
 impl LimitsType {
@@ -66,7 +68,8 @@ pub struct PreLimits {
     pub mate: Option<u16>,
     pub infinite: bool,
     pub ponder: bool,
-    pub search_moves: Vec<String>,
+    /// The moves of `searchmoves`, if the search is restricted to them.
+    pub search_moves: Option<Vec<String>>,
 }
 
 impl PreLimits {
@@ -96,9 +99,9 @@ impl PreLimits {
         }
         println!("infinite: {}", self.infinite);
         println!("ponder: {}", self.ponder);
-        if self.search_moves.len() > 1 {
+        if let Some(ref search_moves) = self.search_moves {
             print!("search_moves:");
-            self.search_moves.iter().for_each(|p| print!(" {}", p));
+            search_moves.iter().for_each(|p| print!(" {}", p));
             println!();
         }
     }
@@ -111,20 +114,30 @@ impl PreLimits {
             mate: None,
             infinite: false,
             ponder: false,
-            search_moves: Vec::new(),
+            search_moves: None,
         }
     }
 
+    /// Creates the `Limits` of a search. The moves of `searchmoves` have to be checked against
+    /// the board first, so the `Limits` are left searching every move.
     pub fn create(self) -> Limits {
         let mut limits = Limits {
-            search_moves: self.search_moves.clone(),
+            search_moves: Vec::new(),
             limits_type: LimitsType::Infinite,
             start: time::Instant::now(),
+            multi_pv: 1,
+            ponder: self.ponder,
+            infinite: self.infinite,
+            use_ponder: false,
+            skill_level: MAX_SKILL_LEVEL,
+            contempt: 0,
+            show_wdl: false,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            slow_mover: DEFAULT_SLOW_MOVER,
+            nodes_time: 0,
         };
 
-        limits.limits_type = if self.ponder {
-            LimitsType::Ponder
-        } else if let Some(m_time) = self.move_time {
+        limits.limits_type = if let Some(m_time) = self.move_time {
             LimitsType::MoveTime(m_time)
         } else if let Some(mate) = self.mate {
             LimitsType::Mate(mate)
@@ -145,9 +158,19 @@ impl PreLimits {
 
 #[derive(Clone)]
 pub struct Limits {
-    pub search_moves: Vec<String>,
+    pub search_moves: Vec<BitMove>, // legal moves to search at the root, or every move if empty
     pub limits_type: LimitsType,
     pub start: time::Instant,
+    pub multi_pv: usize,    // number of principal variations to search
+    pub ponder: bool,       // start the search in ponder mode
+    pub infinite: bool,     // keep the best move until told to stop, as for "go infinite"
+    pub use_ponder: bool,   // the Ponder option is on, so the opponent's time is used too
+    pub skill_level: u8,    // strength of play, from 0 up to full strength at MAX_SKILL_LEVEL
+    pub contempt: i32,      // centipawns a draw is worth less to the side to move at the root
+    pub show_wdl: bool,     // report the win, draw and loss chances of the score
+    pub move_overhead: i64, // msec kept in reserve for each move
+    pub slow_mover: i64,    // importance of the current move to the time management
+    pub nodes_time: i64,    // nodes per msec to use as time, or 0 to use the clock
 }
 
 impl Limits {
@@ -163,6 +186,16 @@ impl Limits {
             search_moves: Vec::new(),
             limits_type: LimitsType::Infinite,
             start: time::Instant::now(),
+            multi_pv: 1,
+            ponder: false,
+            infinite: false,
+            use_ponder: false,
+            skill_level: MAX_SKILL_LEVEL,
+            contempt: 0,
+            show_wdl: false,
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+            slow_mover: DEFAULT_SLOW_MOVER,
+            nodes_time: 0,
         }
     }
 
@@ -178,4 +211,11 @@ impl Limits {
             _ => None,
         }
     }
+
+    pub fn use_nodes(&self) -> Option<u64> {
+        match self.limits_type {
+            LimitsType::Nodes(nodes) => Some(nodes),
+            _ => None,
+        }
+    }
 }
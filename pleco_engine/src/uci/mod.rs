@@ -1,3 +1,5 @@
 /// uci protocol functions
 pub mod options;
+pub mod output;
 pub mod parse;
+pub mod wdl;
@@ -1,6 +1,11 @@
 //! Houses any UCI compatible options, as well as the framework for parsing and applying them.
 
-use consts::{DEFAULT_TT_SIZE, MAX_PV, MAX_THREADS};
+use consts::{
+    DEFAULT_BOOK_DEPTH, DEFAULT_TT_SIZE, MAX_CONTEMPT, MAX_ELO, MAX_PV, MAX_SKILL_LEVEL,
+    MAX_THREADS, MIN_ELO, NO_BOOK_FILE,
+};
+
+use time::time_management::{DEFAULT_MOVE_OVERHEAD, DEFAULT_SLOW_MOVER};
 
 use std::collections::VecDeque;
 use std::option::Option;
@@ -13,6 +18,29 @@ pub enum OptionWork {
     ResizeTT(usize),
     MultiPV(usize),
     Threads(usize),
+    OwnBook(bool),
+    BookFile(String),
+    BookDepth(u16),
+    BookVariety(bool),
+    Ponder(bool),
+    SkillLevel(u8),
+    LimitStrength(bool),
+    Elo(u16),
+    Contempt(i32),
+    AnalysisContempt(AnalysisContempt),
+    ShowWDL(bool),
+    MoveOverhead(i64),
+    SlowMover(i64),
+    NodesTime(i64),
+}
+
+/// The sides contempt is used for while analyzing with `go infinite`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AnalysisContempt {
+    Off,
+    White,
+    Black,
+    Both,
 }
 
 impl OptionWork {
@@ -22,6 +50,20 @@ impl OptionWork {
             OptionWork::ResizeTT(_) => false,
             OptionWork::MultiPV(_) => false,
             OptionWork::Threads(_) => false,
+            OptionWork::OwnBook(_) => true,
+            OptionWork::BookFile(_) => true,
+            OptionWork::BookDepth(_) => true,
+            OptionWork::BookVariety(_) => true,
+            OptionWork::Ponder(_) => true,
+            OptionWork::SkillLevel(_) => true,
+            OptionWork::LimitStrength(_) => true,
+            OptionWork::Elo(_) => true,
+            OptionWork::Contempt(_) => true,
+            OptionWork::AnalysisContempt(_) => true,
+            OptionWork::ShowWDL(_) => true,
+            OptionWork::MoveOverhead(_) => true,
+            OptionWork::SlowMover(_) => true,
+            OptionWork::NodesTime(_) => true,
         }
     }
 }
@@ -41,6 +83,20 @@ impl OptionsMap {
         map.push(OptionsMap::resize_hash());
         map.push(OptionsMap::threads());
         map.push(OptionsMap::multi_pv());
+        map.push(OptionsMap::own_book());
+        map.push(OptionsMap::book_file());
+        map.push(OptionsMap::book_depth());
+        map.push(OptionsMap::book_variety());
+        map.push(OptionsMap::ponder());
+        map.push(OptionsMap::skill_level());
+        map.push(OptionsMap::limit_strength());
+        map.push(OptionsMap::elo());
+        map.push(OptionsMap::contempt());
+        map.push(OptionsMap::analysis_contempt());
+        map.push(OptionsMap::show_wdl());
+        map.push(OptionsMap::move_overhead());
+        map.push(OptionsMap::slow_mover());
+        map.push(OptionsMap::nodes_time());
         map.sort_by(|a, b| a.option_name().cmp(b.option_name()));
 
         OptionsMap { map, work }
@@ -62,9 +118,16 @@ impl OptionsMap {
 
     /// Displays all available options in alphabetical order
     pub fn display_all(&self) {
-        for op in self.map.iter() {
-            println!("{}", op.display());
-        }
+        println!("{}", self.display_string());
+    }
+
+    /// Returns the display of all available options in alphabetical order, one per line.
+    pub fn display_string(&self) -> String {
+        self.map
+            .iter()
+            .map(|op| op.display())
+            .collect::<Vec<String>>()
+            .join("\n")
     }
 
     /// Returns if there is any work available from the `OptionsMap`.
@@ -91,6 +154,15 @@ impl OptionsMap {
         })
     }
 
+    fn ponder() -> Box<UCIOption> {
+        let mutator: fn(bool) -> Option<OptionWork> = |x: bool| Some(OptionWork::Ponder(x));
+        Box::new(UCICheck {
+            option_name: "Ponder",
+            default: false,
+            mutator,
+        })
+    }
+
     fn resize_hash() -> Box<UCIOption> {
         let mutator: fn(i32) -> Option<OptionWork> =
             |x: i32| Some(OptionWork::ResizeTT(x as usize));
@@ -103,6 +175,148 @@ impl OptionsMap {
         })
     }
 
+    fn own_book() -> Box<UCIOption> {
+        let mutator: fn(bool) -> Option<OptionWork> = |x: bool| Some(OptionWork::OwnBook(x));
+        Box::new(UCICheck {
+            option_name: "OwnBook",
+            default: false,
+            mutator,
+        })
+    }
+
+    fn book_file() -> Box<UCIOption> {
+        let mutator: fn(&str) -> Option<OptionWork> =
+            |x: &str| Some(OptionWork::BookFile(x.to_string()));
+        Box::new(UCIText {
+            option_name: "Book File",
+            default: NO_BOOK_FILE,
+            mutator,
+        })
+    }
+
+    fn book_depth() -> Box<UCIOption> {
+        let mutator: fn(i32) -> Option<OptionWork> = |x: i32| Some(OptionWork::BookDepth(x as u16));
+        Box::new(UCISpin {
+            option_name: "Book Depth",
+            default: DEFAULT_BOOK_DEPTH as i32,
+            min: 1,
+            max: 255,
+            mutator,
+        })
+    }
+
+    fn book_variety() -> Box<UCIOption> {
+        let mutator: fn(bool) -> Option<OptionWork> = |x: bool| Some(OptionWork::BookVariety(x));
+        Box::new(UCICheck {
+            option_name: "Book Variety",
+            default: false,
+            mutator,
+        })
+    }
+
+    fn skill_level() -> Box<UCIOption> {
+        let mutator: fn(i32) -> Option<OptionWork> = |x: i32| Some(OptionWork::SkillLevel(x as u8));
+        Box::new(UCISpin {
+            option_name: "Skill Level",
+            default: MAX_SKILL_LEVEL as i32,
+            min: 0,
+            max: MAX_SKILL_LEVEL as i32,
+            mutator,
+        })
+    }
+
+    fn limit_strength() -> Box<UCIOption> {
+        let mutator: fn(bool) -> Option<OptionWork> = |x: bool| Some(OptionWork::LimitStrength(x));
+        Box::new(UCICheck {
+            option_name: "UCI_LimitStrength",
+            default: false,
+            mutator,
+        })
+    }
+
+    fn elo() -> Box<UCIOption> {
+        let mutator: fn(i32) -> Option<OptionWork> = |x: i32| Some(OptionWork::Elo(x as u16));
+        Box::new(UCISpin {
+            option_name: "UCI_Elo",
+            default: MIN_ELO,
+            min: MIN_ELO,
+            max: MAX_ELO,
+            mutator,
+        })
+    }
+
+    fn contempt() -> Box<UCIOption> {
+        let mutator: fn(i32) -> Option<OptionWork> = |x: i32| Some(OptionWork::Contempt(x));
+        Box::new(UCISpin {
+            option_name: "Contempt",
+            default: 0,
+            min: -MAX_CONTEMPT,
+            max: MAX_CONTEMPT,
+            mutator,
+        })
+    }
+
+    fn analysis_contempt() -> Box<UCIOption> {
+        let mutator: fn(&str) -> Option<OptionWork> = |x: &str| {
+            let side = match x {
+                "Off" => AnalysisContempt::Off,
+                "White" => AnalysisContempt::White,
+                "Black" => AnalysisContempt::Black,
+                _ => AnalysisContempt::Both,
+            };
+            Some(OptionWork::AnalysisContempt(side))
+        };
+        Box::new(UCICombo {
+            option_name: "Analysis Contempt",
+            default: "Both",
+            values: &["Off", "White", "Black", "Both"],
+            mutator,
+        })
+    }
+
+    fn show_wdl() -> Box<UCIOption> {
+        let mutator: fn(bool) -> Option<OptionWork> = |x: bool| Some(OptionWork::ShowWDL(x));
+        Box::new(UCICheck {
+            option_name: "UCI_ShowWDL",
+            default: false,
+            mutator,
+        })
+    }
+
+    fn move_overhead() -> Box<UCIOption> {
+        let mutator: fn(i32) -> Option<OptionWork> =
+            |x: i32| Some(OptionWork::MoveOverhead(x as i64));
+        Box::new(UCISpin {
+            option_name: "Move Overhead",
+            default: DEFAULT_MOVE_OVERHEAD as i32,
+            min: 0,
+            max: 5000,
+            mutator,
+        })
+    }
+
+    fn slow_mover() -> Box<UCIOption> {
+        let mutator: fn(i32) -> Option<OptionWork> = |x: i32| Some(OptionWork::SlowMover(x as i64));
+        Box::new(UCISpin {
+            option_name: "Slow Mover",
+            default: DEFAULT_SLOW_MOVER as i32,
+            min: 10,
+            max: 1000,
+            mutator,
+        })
+    }
+
+    fn nodes_time() -> Box<UCIOption> {
+        let mutator: fn(i32) -> Option<OptionWork> = |x: i32| Some(OptionWork::NodesTime(x as i64));
+        Box::new(UCISpin {
+            option_name: "nodestime",
+            default: 0,
+            min: 0,
+            max: 10000,
+            mutator,
+        })
+    }
+
     fn threads() -> Box<UCIOption> {
         let mutator: fn(i32) -> Option<OptionWork> = |x: i32| Some(OptionWork::Threads(x as usize));
         Box::new(UCISpin {
@@ -0,0 +1,84 @@
+//! Where the engine writes its side of the protocol.
+//!
+//! Both the thread reading commands and the main search thread write to the same `Output`,
+//! so each line is written and flushed while holding a lock, keeping lines from interleaving.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// A shared, line based writer. Cloning an `Output` gives another handle to the same writer.
+#[derive(Clone)]
+pub struct Output {
+    writer: Option<Arc<Mutex<Box<Write + Send>>>>,
+}
+
+impl Output {
+    /// Creates an `Output` writing to `writer`.
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Output {
+            writer: Some(Arc::new(Mutex::new(Box::new(writer)))),
+        }
+    }
+
+    /// Creates an `Output` writing to standard out.
+    pub fn stdout() -> Self {
+        Output::new(io::stdout())
+    }
+
+    /// Creates an `Output` that discards everything written to it.
+    pub fn none() -> Self {
+        Output { writer: None }
+    }
+
+    /// Returns if anything written is actually going somewhere.
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Writes `text` followed by a newline, and flushes it so the other side sees it right away.
+    ///
+    /// Errors are ignored, as a GUI that went away has nothing left to report them to.
+    pub fn line(&self, text: &str) {
+        if let Some(ref writer) = self.writer {
+            if let Ok(mut writer) = writer.lock() {
+                let _ = writeln!(writer, "{}", text);
+                let _ = writer.flush();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn output_lines() {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let output = Output::new(Shared(buffer.clone()));
+        assert!(output.is_enabled());
+        output.line("readyok");
+        output.clone().line("bestmove e2e4");
+        assert_eq!(
+            &buffer.lock().unwrap()[..],
+            &b"readyok\nbestmove e2e4\n"[..]
+        );
+
+        let none = Output::none();
+        assert!(!none.is_enabled());
+        none.line("ignored");
+    }
+}
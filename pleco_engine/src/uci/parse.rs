@@ -1,9 +1,29 @@
 //! Functions for parsing UCI input, including both time data & the position of the board to be searched.
 
-use pleco::Board;
+use std::fmt;
+
+use pleco::{Board, MoveList};
 
 use time::uci_timer::{PreLimits, UCITimer};
 
+/// The reason the moves of `searchmoves` can't be searched.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SearchMovesError {
+    /// No moves were given.
+    Empty,
+    /// A move that isn't legal on the board.
+    Illegal(String),
+}
+
+impl fmt::Display for SearchMovesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SearchMovesError::Empty => write!(f, "no moves given to searchmoves"),
+            SearchMovesError::Illegal(ref mov) => write!(f, "illegal move in searchmoves: {}", mov),
+        }
+    }
+}
+
 fn is_keyword(arg: &str) -> bool {
     match arg {
         "searchmoves" | "ponder" | "wtime" | "btime" | "winc" | "binc" | "movestogo" | "depth"
@@ -110,9 +130,10 @@ pub fn parse_time(args: &[&str]) -> PreLimits {
                 }
             }
             "searchmoves" => 'searchmoves: loop {
+                let search_moves = limit.search_moves.get_or_insert_with(Vec::new);
                 if let Some(mov) = args.get(token_idx + 1) {
                     if !is_keyword(mov) {
-                        limit.search_moves.push((*mov).to_string());
+                        search_moves.push((*mov).to_string());
                         token_idx += 1;
                     } else {
                         break 'searchmoves;
@@ -149,6 +170,28 @@ fn valid_move(board: &mut Board, mov: &str) -> bool {
     false
 }
 
+/// Parses the moves of a `go searchmoves` command against a board, returning the moves to
+/// search. It's an error if no moves are given, or if any of them isn't legal.
+pub fn parse_search_moves(board: &Board, moves: &[String]) -> Result<MoveList, SearchMovesError> {
+    if moves.is_empty() {
+        return Err(SearchMovesError::Empty);
+    }
+    let legal_moves = board.generate_moves();
+
+    let mut search_moves = MoveList::default();
+    for mov in moves.iter() {
+        match legal_moves.iter().find(|m| board.uci_move(**m) == *mov) {
+            Some(m) => {
+                if !search_moves.contains(m) {
+                    search_moves.push(*m);
+                }
+            }
+            None => return Err(SearchMovesError::Illegal(mov.clone())),
+        }
+    }
+    Ok(search_moves)
+}
+
 pub fn setboard_parse_board(args: &[&str]) -> Option<Board> {
     let fen_string: String = args
         .iter()
@@ -160,7 +203,7 @@ pub fn setboard_parse_board(args: &[&str]) -> Option<Board> {
 }
 
 pub fn position_parse_board(args: &[&str]) -> Option<Board> {
-    let start: &str = args[0];
+    let start: &str = args.first()?;
     let mut board = if start == "startpos" {
         Some(Board::start_pos())
     } else if start == "fen" {
@@ -222,7 +265,32 @@ mod tests {
         let t_str = "go infinite searchmoves e2e4 d2d4";
         let args: Vec<&str> = t_str.split_whitespace().collect();
         let time = parse_time(&args[1..]);
-        assert_eq!(time.search_moves.len(), 2);
+        let search_moves = time.search_moves.unwrap();
+        assert_eq!(search_moves.len(), 2);
+
+        let board = Board::start_pos();
+        let moves = parse_search_moves(&board, &search_moves).unwrap();
+        assert_eq!(moves.len(), 2);
+        assert_eq!(moves[0].stringify(), "e2e4");
+        assert_eq!(
+            parse_search_moves(&board, &[]).err(),
+            Some(SearchMovesError::Empty)
+        );
+        let args = ["searchmoves", "depth", "3"];
+        assert_eq!(parse_time(&args).search_moves, Some(Vec::new()));
+        assert_eq!(parse_time(&args[1..]).search_moves, None);
+
+        let p_str = "go ponder wtime 1000 btime 1000";
+        let args: Vec<&str> = p_str.split_whitespace().collect();
+        let limits = parse_time(&args[1..]).create();
+        assert!(limits.ponder);
+        assert!(limits.use_time_management().is_some());
+
+        let illegal = vec!["e2e4".to_string(), "e2e5".to_string()];
+        assert_eq!(
+            parse_search_moves(&board, &illegal).err(),
+            Some(SearchMovesError::Illegal("e2e5".to_string()))
+        );
     }
 
     #[test]
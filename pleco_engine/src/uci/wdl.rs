@@ -0,0 +1,86 @@
+//! A model of the win, draw and loss chances of a score, for `UCI_ShowWDL`.
+//!
+//! The chance of winning with a score of `x` centipawns is given by the logistic function
+//! `1 / (1 + e^((a - x) / b))`, and the chance of losing is that of the opponent winning with
+//! `-x`. `a` is the score at which a win is as likely as not, and `b` is how quickly the chance
+//! of winning grows with the score. Both change over the course of a game, so they are cubic
+//! polynomials of `ply / 64`.
+//!
+//! The coefficients are fitted to the results of games played by the `selfplay` example, using
+//! the `fit_wdl` example. It prints updated values of `WIN_RATE_A` and `WIN_RATE_B`, as well as
+//! of `MIN_MODEL_PLY` and `MAX_MODEL_PLY`.
+
+use pleco::core::score::*;
+
+/// Coefficients of the polynomial for `a`, from the highest order to the constant term.
+pub const WIN_RATE_A: [f64; 4] = [3.37, 53.56, -48.65, 83.81];
+
+/// Coefficients of the polynomial for `b`, from the highest order to the constant term.
+pub const WIN_RATE_B: [f64; 4] = [-4.54, 88.14, -239.00, 294.44];
+
+/// Plies before which the model no longer changes, as there is no data for them.
+pub const MIN_MODEL_PLY: u16 = 0;
+
+/// Plies after which the model no longer changes, as there is too little data for them.
+pub const MAX_MODEL_PLY: u16 = 223;
+
+/// Scores are clamped to this many centipawns before applying the model.
+const MAX_MODEL_CP: f64 = 4000.0;
+
+/// Returns the parameters `a` and `b` of the model at a given game ply.
+pub fn parameters(ply: u16) -> (f64, f64) {
+    let m = ply.max(MIN_MODEL_PLY).min(MAX_MODEL_PLY) as f64 / 64.0;
+    (polynomial(&WIN_RATE_A, m), polynomial(&WIN_RATE_B, m))
+}
+
+/// Returns the chance of winning, between 0 and 1, with a score of `cp` centipawns and the
+/// model parameters `a` and `b`.
+pub fn win_probability(cp: f64, a: f64, b: f64) -> f64 {
+    let x = cp.max(-MAX_MODEL_CP).min(MAX_MODEL_CP);
+    1.0 / (1.0 + ((a - x) / b).exp())
+}
+
+/// Returns the chance of the side to move winning in permille, given the score of the
+/// position and the game ply.
+pub fn win_rate(value: Value, ply: u16) -> u32 {
+    let (a, b) = parameters(ply);
+    let cp = value as f64 * 100.0 / PAWN_EG as f64;
+    (0.5 + 1000.0 * win_probability(cp, a, b)) as u32
+}
+
+/// Returns the chances of the side to move winning, drawing and losing in permille,
+/// given the score of the position and the game ply.
+pub fn wdl(value: Value, ply: u16) -> (u32, u32, u32) {
+    let win = win_rate(value, ply);
+    let loss = win_rate(-value, ply).min(1000 - win);
+    (win, 1000 - win - loss, loss)
+}
+
+// Evaluates a polynomial with Horner's method.
+fn polynomial(coefficients: &[f64; 4], m: f64) -> f64 {
+    coefficients.iter().fold(0.0, |acc, c| acc * m + c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wdl_model() {
+        for ply in [0, 20, 60, 120, 300].iter() {
+            let (w, d, l) = wdl(0, *ply);
+            assert_eq!(w, l);
+            assert_eq!(w + d + l, 1000);
+
+            let mut prev_win = 0;
+            for cp in (-30..30).map(|p| p * 50) {
+                let (w, d, l) = wdl(cp * PAWN_EG / 100, *ply);
+                assert!(w >= prev_win);
+                assert_eq!(w + d + l, 1000);
+                prev_win = w;
+            }
+        }
+        assert_eq!(wdl(MATE - 5, 40), (1000, 0, 0));
+        assert_eq!(wdl(-MATE + 5, 40), (0, 0, 1000));
+    }
+}
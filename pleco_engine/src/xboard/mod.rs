@@ -0,0 +1,396 @@
+//! The Chess Engine Communication Protocol (CECP), spoken by XBoard, WinBoard and older GUIs.
+//!
+//! `PlecoSearcher::uci_io` hands its input over to `run` when the first command is "xboard".
+//! The searcher does the searching as it does for UCI, but its UCI output is switched off, and
+//! the thinking output and the moves of the engine are written by an `InfoSink` instead.
+
+pub mod parse;
+
+use std::io::BufRead;
+use std::sync::{Arc, Mutex};
+
+use pleco::{BitMove, Board, Player};
+
+use engine::{PlecoSearcher, ID_NAME, VERSION};
+use search::info::{InfoSink, SearchInfo, SearchResult};
+use time::uci_timer::{PreLimits, UCITimer};
+use uci::output::Output;
+use uci::parse::setboard_parse_board;
+
+use self::parse::Level;
+
+/// The time control until the GUI sets one, which is the default of XBoard.
+const DEFAULT_LEVEL: Level = Level {
+    moves_per_session: 40,
+    base_msec: 300_000,
+    inc_msec: 0,
+};
+
+/// State shared with the `Thinking` sink, which runs on the main search thread.
+struct Shared {
+    /// If true, the thinking output is written.
+    post: bool,
+    /// If true, the engine plays the best move once the search is over.
+    play: bool,
+    /// The move the engine has played, which is yet to be made on the board.
+    played: Option<BitMove>,
+    /// The board being searched, for writing its moves.
+    board: Board,
+}
+
+/// Writes the thinking output and the moves of the engine.
+struct Thinking {
+    output: Output,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl InfoSink for Thinking {
+    // "[ply] [score] [time in centiseconds] [nodes] [pv]"
+    fn info(&self, info: &SearchInfo) {
+        let shared = match self.shared.lock() {
+            Ok(shared) => shared,
+            Err(_) => return,
+        };
+        if let (true, Some(line)) = (shared.post, info.lines.first()) {
+            let pv = line
+                .pv
+                .iter()
+                .map(|mov| shared.board.uci_move(*mov))
+                .collect::<Vec<String>>()
+                .join(" ");
+            self.output.line(&format!(
+                "{} {} {} {} {}",
+                line.depth,
+                parse::score(line.score),
+                info.time / 10,
+                info.nodes,
+                pv
+            ));
+        }
+    }
+
+    fn result(&self, result: &SearchResult) {
+        if let Ok(mut shared) = self.shared.lock() {
+            if shared.play && !result.best_move.is_null() {
+                shared.play = false;
+                shared.played = Some(result.best_move);
+                let mov = shared.board.uci_move(result.best_move);
+                self.output.line(&format!("move {}", mov));
+            }
+        }
+    }
+}
+
+struct XBoard<'a> {
+    searcher: &'a mut PlecoSearcher,
+    output: Output,
+    shared: Arc<Mutex<Shared>>,
+    board: Board,
+    /// Moves made since the board was set, which are the moves that can be taken back.
+    history: usize,
+    /// The side the engine plays, or `None` in force mode.
+    engine_side: Option<Player>,
+    analyzing: bool,
+    level: Level,
+    move_time: Option<u64>,
+    depth: Option<u16>,
+    /// Time left on the clock of the engine, in centiseconds.
+    time: i64,
+    /// Time left on the clock of the opponent, in centiseconds.
+    otim: i64,
+}
+
+/// Runs the CECP protocol, reading commands from `input` and writing to the output of the
+/// searcher. Returns after a "quit" command or at the end of the input.
+pub fn run<R: BufRead>(searcher: &mut PlecoSearcher, mut input: R) {
+    let mut xboard = XBoard::new(searcher);
+    let mut full_command = String::new();
+    loop {
+        full_command.clear();
+        match input.read_line(&mut full_command) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if !xboard.command(&full_command) {
+            break;
+        }
+    }
+    xboard.finish();
+}
+
+impl<'a> XBoard<'a> {
+    fn new(searcher: &'a mut PlecoSearcher) -> Self {
+        let output = searcher.threadpool().output.clone();
+        let shared = Arc::new(Mutex::new(Shared {
+            post: false,
+            play: false,
+            played: None,
+            board: Board::start_pos(),
+        }));
+        searcher.threadpool().set_output(Output::none());
+        searcher.set_info_sink(Some(Box::new(Thinking {
+            output: output.clone(),
+            shared: shared.clone(),
+        })));
+        XBoard {
+            searcher,
+            output,
+            shared,
+            board: Board::start_pos(),
+            history: 0,
+            engine_side: Some(Player::Black),
+            analyzing: false,
+            level: DEFAULT_LEVEL,
+            move_time: None,
+            depth: None,
+            time: DEFAULT_LEVEL.base_msec / 10,
+            otim: DEFAULT_LEVEL.base_msec / 10,
+        }
+    }
+
+    /// Handles a single command. Returns false once the engine should quit.
+    fn command(&mut self, full_command: &str) -> bool {
+        let args: Vec<&str> = full_command.split_whitespace().collect();
+        let command: &str = args.first().unwrap_or(&"");
+        match command {
+            "quit" => return false,
+            "protover" => self.features(),
+            "new" => self.new_game(),
+            "force" => {
+                self.stop();
+                self.engine_side = None;
+            }
+            "go" => {
+                self.stop();
+                self.analyzing = false;
+                self.engine_side = Some(self.board.turn());
+                self.think();
+            }
+            "?" => self.searcher.halt(),
+            "usermove" => match args.get(1) {
+                Some(mov) => self.user_move(mov),
+                None => self.error("usermove needs a move", command),
+            },
+            "level" => match parse::parse_level(&args[1..]) {
+                Some(level) => {
+                    self.level = level;
+                    self.move_time = None;
+                    self.time = level.base_msec / 10;
+                    self.otim = level.base_msec / 10;
+                }
+                None => self.error("bad level", full_command.trim()),
+            },
+            "st" => match args.get(1).and_then(|s| s.parse::<u64>().ok()) {
+                Some(seconds) => self.move_time = Some(seconds * 1000),
+                None => self.error("bad st", full_command.trim()),
+            },
+            "sd" => match args.get(1).and_then(|s| s.parse::<u16>().ok()) {
+                Some(depth) => self.depth = Some(depth),
+                None => self.error("bad sd", full_command.trim()),
+            },
+            "time" => {
+                if let Some(time) = args.get(1).and_then(|s| s.parse::<i64>().ok()) {
+                    self.time = time;
+                }
+            }
+            "otim" => {
+                if let Some(otim) = args.get(1).and_then(|s| s.parse::<i64>().ok()) {
+                    self.otim = otim;
+                }
+            }
+            "undo" => self.take_back(command, 1),
+            "remove" => self.take_back(command, 2),
+            "post" | "nopost" => {
+                if let Ok(mut shared) = self.shared.lock() {
+                    shared.post = command == "post";
+                }
+            }
+            "analyze" => {
+                self.stop();
+                self.analyzing = true;
+                self.engine_side = None;
+                self.analyze();
+            }
+            "exit" => {
+                self.stop();
+                self.analyzing = false;
+            }
+            "setboard" => self.set_board(&args[1..]),
+            "ping" => self
+                .output
+                .line(&format!("pong {}", args.get(1).unwrap_or(&""))),
+            "result" => {
+                self.stop();
+                self.engine_side = None;
+            }
+            // Commands that don't change anything for this engine.
+            "" | "xboard" | "accepted" | "rejected" | "random" | "easy" | "hard" | "computer"
+            | "name" | "rating" | "ics" | "post_mode" | "." => {}
+            _ => {
+                // Without the usermove feature, moves are sent on their own. They follow the
+                // move of the engine, which may not be on the board yet.
+                self.apply_played();
+                if parse::parse_move(&self.board, command).is_some() {
+                    self.user_move(command);
+                } else {
+                    self.error("unknown command", command);
+                }
+            }
+        }
+        true
+    }
+
+    fn features(&self) {
+        self.output.line(&format!(
+            "feature ping=1 setboard=1 usermove=1 time=1 draw=0 sigint=0 sigterm=0 reuse=1 \
+             analyze=1 colors=0 san=0 myname=\"{} {}\" variants=\"normal\" done=1",
+            ID_NAME, VERSION
+        ));
+    }
+
+    fn error(&self, kind: &str, command: &str) {
+        self.output.line(&format!("Error ({}): {}", kind, command));
+    }
+
+    fn new_game(&mut self) {
+        self.stop();
+        self.searcher.clear_search();
+        self.board = Board::start_pos();
+        self.history = 0;
+        self.engine_side = Some(Player::Black);
+        self.level = DEFAULT_LEVEL;
+        self.move_time = None;
+        self.depth = None;
+        self.time = DEFAULT_LEVEL.base_msec / 10;
+        self.otim = DEFAULT_LEVEL.base_msec / 10;
+        self.analyze();
+    }
+
+    fn user_move(&mut self, mov: &str) {
+        self.stop();
+        match parse::parse_move(&self.board, mov) {
+            Some(bit_move) => {
+                self.board.apply_move(bit_move);
+                self.history += 1;
+                if self.analyzing {
+                    self.analyze();
+                } else {
+                    self.think();
+                }
+            }
+            None => self.output.line(&format!("Illegal move: {}", mov)),
+        }
+    }
+
+    fn take_back(&mut self, command: &str, moves: usize) {
+        self.stop();
+        if self.history < moves {
+            self.error("no moves to take back", command);
+            return;
+        }
+        for _ in 0..moves {
+            self.board.undo_move();
+        }
+        self.history -= moves;
+        self.analyze();
+    }
+
+    fn set_board(&mut self, args: &[&str]) {
+        self.stop();
+        match setboard_parse_board(args) {
+            Some(board) => match board.is_okay() {
+                Ok(()) => {
+                    self.board = board;
+                    self.history = 0;
+                    self.analyze();
+                }
+                Err(_) => self.output.line("tellusererror Illegal position"),
+            },
+            None => self.output.line("tellusererror Illegal position"),
+        }
+    }
+
+    /// Starts searching for a move to play, if it is the turn of the engine.
+    fn think(&mut self) {
+        if self.engine_side != Some(self.board.turn()) {
+            return;
+        }
+        if self.board.generate_moves().is_empty() {
+            self.game_over();
+            return;
+        }
+        let mut limit = PreLimits::blank();
+        limit.move_time = self.move_time;
+        limit.depth = self.depth;
+        let us = self.board.turn() as usize;
+        let mut timer = UCITimer::blank();
+        timer.time_msec[us] = self.time * 10;
+        timer.time_msec[1 - us] = self.otim * 10;
+        timer.inc_msec = [self.level.inc_msec; 2];
+        if self.level.moves_per_session > 0 {
+            let moves = u32::from(self.board.moves_played() / 2);
+            timer.moves_to_go = self.level.moves_per_session - moves % self.level.moves_per_session;
+        }
+        limit.time = Some(timer);
+        self.search(limit, true);
+    }
+
+    /// Restarts the analysis of the board, when in analyze mode.
+    fn analyze(&mut self) {
+        if self.analyzing {
+            let mut limit = PreLimits::blank();
+            limit.infinite = true;
+            self.search(limit, false);
+        }
+    }
+
+    fn search(&mut self, limit: PreLimits, play: bool) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.play = play;
+            shared.board = self.board.shallow_clone();
+        }
+        // Without any searchmoves, there is nothing for the search to fail on.
+        let _ = self.searcher.search(&self.board, &limit);
+    }
+
+    /// Stops the current search without playing a move, and makes the move the engine
+    /// has already played, if any.
+    fn stop(&mut self) {
+        // Once `play` is unset, the sink won't play a move, so `played` is final.
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.play = false;
+        }
+        self.searcher.halt();
+        self.searcher.threadpool().wait_for_finish();
+        self.apply_played();
+    }
+
+    /// Makes the move the engine has played, if there is one, without stopping the search.
+    fn apply_played(&mut self) {
+        let played = match self.shared.lock() {
+            Ok(mut shared) => shared.played.take(),
+            Err(_) => None,
+        };
+        if let Some(mov) = played {
+            self.board.apply_move(mov);
+            self.history += 1;
+        }
+    }
+
+    fn game_over(&self) {
+        if !self.board.in_check() {
+            self.output.line("1/2-1/2 {Stalemate}");
+        } else if self.board.turn() == Player::White {
+            self.output.line("0-1 {Black mates}");
+        } else {
+            self.output.line("1-0 {White mates}");
+        }
+    }
+
+    /// Stops searching, and gives the output of the searcher back to UCI.
+    fn finish(&mut self) {
+        self.stop();
+        self.searcher.set_info_sink(None);
+        self.searcher.threadpool().set_output(self.output.clone());
+    }
+}
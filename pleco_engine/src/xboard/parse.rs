@@ -0,0 +1,92 @@
+//! Functions for parsing CECP input, and formatting the scores sent back.
+
+use pleco::{BitMove, Board};
+
+use search::info::SearchScore;
+
+/// Scores of mate are sent as this, plus or minus the number of moves until mate.
+const MATE_SCORE: i32 = 100_000;
+
+/// A time control, as set by the "level" command.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Level {
+    /// Moves to play in each time control, or 0 if the time is for the rest of the game.
+    pub moves_per_session: u32,
+    /// Time for each time control, in msec.
+    pub base_msec: i64,
+    /// Time added after each move, in msec.
+    pub inc_msec: i64,
+}
+
+// "level" "[moves per session]" "[minutes | minutes:seconds]" "[increment seconds]"
+pub fn parse_level(args: &[&str]) -> Option<Level> {
+    if args.len() < 3 {
+        return None;
+    }
+    let moves_per_session = args[0].parse::<u32>().ok()?;
+    let mut base = args[1].split(':');
+    let minutes = base.next()?.parse::<f64>().ok()?;
+    let seconds = match base.next() {
+        Some(seconds) => seconds.parse::<f64>().ok()?,
+        None => 0.0,
+    };
+    let inc = args[2].parse::<f64>().ok()?;
+    Some(Level {
+        moves_per_session,
+        base_msec: ((minutes * 60.0 + seconds) * 1000.0) as i64,
+        inc_msec: (inc * 1000.0) as i64,
+    })
+}
+
+/// Parses a move sent by the GUI, which is normally in coordinate notation ("e2e4", "e7e8q"),
+/// though SAN is accepted as well. Returns `None` if the move isn't legal on the board.
+pub fn parse_move(board: &Board, mov: &str) -> Option<BitMove> {
+    board
+        .generate_moves()
+        .iter()
+        .find(|m| board.uci_move(**m) == mov)
+        .cloned()
+        .or_else(|| board.parse_san(mov).ok())
+}
+
+/// Converts a score into the centipawns used in the thinking output.
+pub fn score(score: SearchScore) -> i32 {
+    match score {
+        SearchScore::Cp(cp) => cp,
+        SearchScore::Mate(moves) if moves > 0 => MATE_SCORE + moves,
+        SearchScore::Mate(moves) => -MATE_SCORE + moves,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        let level = parse_level(&["40", "5", "0"]).unwrap();
+        assert_eq!(level.moves_per_session, 40);
+        assert_eq!(level.base_msec, 300_000);
+        assert_eq!(level.inc_msec, 0);
+
+        let level = parse_level(&["0", "2:30", "1.5"]).unwrap();
+        assert_eq!(level.moves_per_session, 0);
+        assert_eq!(level.base_msec, 150_000);
+        assert_eq!(level.inc_msec, 1500);
+
+        assert!(parse_level(&["40", "5"]).is_none());
+        assert!(parse_level(&["forty", "5", "0"]).is_none());
+    }
+
+    #[test]
+    fn moves_and_scores() {
+        let board = Board::start_pos();
+        assert_eq!(parse_move(&board, "e2e4").unwrap().stringify(), "e2e4");
+        assert_eq!(parse_move(&board, "Nf3").unwrap().stringify(), "g1f3");
+        assert!(parse_move(&board, "e2e5").is_none());
+
+        assert_eq!(score(SearchScore::Cp(-35)), -35);
+        assert_eq!(score(SearchScore::Mate(3)), 100_003);
+        assert_eq!(score(SearchScore::Mate(-2)), -100_002);
+    }
+}
@@ -0,0 +1,147 @@
+//! Helpers for driving the engine over its protocols, shared by the integration tests.
+
+use std::cell::Cell;
+use std::io::{self, BufReader, Cursor, Read, Write};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use pleco_engine::engine::PlecoSearcher;
+
+/// Collects everything the engine writes, so it can be read while the engine is running.
+#[derive(Clone)]
+pub struct Transcript(Arc<Mutex<Vec<u8>>>);
+
+impl Transcript {
+    fn new() -> Self {
+        Transcript(Arc::new(Mutex::new(Vec::new())))
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Transcript {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Input that blocks until the next command is sent.
+struct Commands {
+    receiver: Receiver<String>,
+    pending: Cursor<Vec<u8>>,
+}
+
+impl Read for Commands {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.position() as usize == self.pending.get_ref().len() {
+            match self.receiver.recv() {
+                Ok(command) => self.pending = Cursor::new((command + "\n").into_bytes()),
+                Err(_) => return Ok(0),
+            }
+        }
+        self.pending.read(buf)
+    }
+}
+
+/// Unoptimized builds of the engine need more stack than a test thread has, so the engine
+/// gets a thread of its own, like the search threads do.
+fn spawn_engine<F: FnOnce(PlecoSearcher) + Send + 'static>(f: F) -> JoinHandle<()> {
+    thread::Builder::new()
+        .stack_size(16 * 1024 * 1024)
+        .spawn(move || f(PlecoSearcher::init(false)))
+        .unwrap()
+}
+
+/// Runs the engine over all of `input`, and returns everything it wrote.
+pub fn run(input: &str) -> String {
+    let transcript = Transcript::new();
+    let output = transcript.clone();
+    let input = input.to_string();
+    spawn_engine(move |mut searcher| searcher.uci_io(Cursor::new(input), output))
+        .join()
+        .unwrap();
+    transcript.text()
+}
+
+/// Drives an engine running on another thread, the way a GUI would.
+pub struct Gui {
+    sender: Sender<String>,
+    transcript: Transcript,
+    engine: JoinHandle<()>,
+    /// The number of lines already looked through by `wait_until`.
+    lines_read: Cell<usize>,
+}
+
+impl Gui {
+    pub fn start() -> Self {
+        let (sender, receiver) = channel();
+        let commands = Commands {
+            receiver,
+            pending: Cursor::new(Vec::new()),
+        };
+        let transcript = Transcript::new();
+        let output = transcript.clone();
+        let engine =
+            spawn_engine(move |mut searcher| searcher.uci_io(BufReader::new(commands), output));
+        Gui {
+            sender,
+            transcript,
+            engine,
+            lines_read: Cell::new(0),
+        }
+    }
+
+    pub fn send(&self, command: &str) {
+        self.sender.send(command.to_string()).unwrap();
+    }
+
+    /// Waits for the next line written by the engine that starts with `prefix`, and returns it.
+    pub fn wait_for(&self, prefix: &str) -> String {
+        self.wait_until(&format!("starting with '{}'", prefix), |line| {
+            line.starts_with(prefix)
+        })
+    }
+
+    /// Waits for the next line written by the engine that `matches`, and returns it. The
+    /// description of the line is used if it never turns up.
+    // Each test crate includes this module, and not all of them use every helper.
+    #[allow(dead_code)]
+    pub fn wait_until<F: Fn(&str) -> bool>(&self, description: &str, matches: F) -> String {
+        let start = Instant::now();
+        loop {
+            let text = self.transcript.text();
+            let found = text
+                .lines()
+                .enumerate()
+                .skip(self.lines_read.get())
+                .find(|&(_, line)| matches(line))
+                .map(|(i, line)| (i, line.to_string()));
+            if let Some((i, line)) = found {
+                self.lines_read.set(i + 1);
+                return line;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(60),
+                "no line {} in:\n{}",
+                description,
+                text
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Ends the input, and returns everything the engine wrote once it's done.
+    pub fn quit(self) -> String {
+        drop(self.sender);
+        self.engine.join().unwrap();
+        self.transcript.text()
+    }
+}
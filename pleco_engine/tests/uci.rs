@@ -0,0 +1,128 @@
+extern crate pleco_engine;
+
+mod common;
+
+use std::thread;
+use std::time::Duration;
+
+use common::*;
+
+use pleco_engine::engine::{ID_AUTHORS, ID_NAME};
+use pleco_engine::uci::options::OptionsMap;
+
+fn startup() -> String {
+    format!(
+        "id name {}\nid authors {}\n{}\nuciok\n",
+        ID_NAME,
+        ID_AUTHORS,
+        OptionsMap::new().display_string()
+    )
+}
+
+#[test]
+fn uci_handshake() {
+    let transcript = run("uci\nisready\nquit\nisready\n");
+    assert_eq!(transcript, startup() + "readyok\n");
+}
+
+#[test]
+fn uci_bad_commands() {
+    let transcript = run("isready\n\
+                          foo bar\n\
+                          setoption nme Hash\n\
+                          position fen 8/8\n\
+                          go depth 3 searchmoves e2e4 e2e5\n\
+                          go searchmoves\n\
+                          isready\n");
+    let expected = "readyok\n\
+                    Unknown Command: foo bar\n\
+                    setoption [name]\n\
+                    unable to parse board\n\
+                    illegal move in searchmoves: e2e5\n\
+                    no moves given to searchmoves\n\
+                    readyok\n";
+    assert_eq!(transcript, expected);
+}
+
+#[test]
+fn uci_game_over() {
+    let transcript = run("position fen 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1\ngo depth 3\n");
+    assert_eq!(transcript, "info depth 0 score cp 0\nbestmove (none)\n");
+}
+
+#[test]
+fn uci_search() {
+    let gui = Gui::start();
+    gui.send("uci");
+    gui.wait_for("uciok");
+    gui.send("setoption name Threads value 1");
+    gui.send("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    gui.send("go depth 4");
+    assert_eq!(gui.wait_for("bestmove"), "bestmove a1a8");
+    gui.send("quit");
+
+    let transcript = gui.quit();
+    assert!(transcript.starts_with(&startup()));
+    assert_eq!(transcript.lines().last(), Some("bestmove a1a8"));
+}
+
+#[test]
+fn uci_hold_best_move() {
+    let gui = Gui::start();
+    gui.send("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+
+    // The mate is found right away, but the best move is held until the GUI asks for it.
+    for &(go, release) in [("go infinite", "stop"), ("go ponder depth 4", "ponderhit")].iter() {
+        gui.send(go);
+        thread::sleep(Duration::from_millis(500));
+        gui.send("isready");
+        gui.wait_for("readyok");
+        gui.send(release);
+        assert_eq!(gui.wait_for("bestmove"), "bestmove a1a8");
+    }
+    gui.send("quit");
+    gui.quit();
+}
+
+#[test]
+fn uci_skill_infinite() {
+    let gui = Gui::start();
+    gui.send("setoption name Skill Level value 0");
+    gui.send("position startpos");
+
+    // The depth of the lowest level doesn't hold back a search until told to stop.
+    gui.send("go infinite");
+    gui.wait_for("info depth 6 ");
+    gui.send("isready");
+    gui.wait_for("readyok");
+    gui.send("stop");
+    gui.wait_for("bestmove");
+    gui.send("quit");
+    gui.quit();
+}
+
+#[test]
+fn uci_options_while_searching() {
+    let gui = Gui::start();
+    gui.send("setoption name Threads value 1");
+    gui.send("position startpos");
+    gui.send("go infinite");
+    gui.wait_for("info depth 6 ");
+
+    // Changing the threads or the table waits for the search to stop, so "stop" still gets
+    // through, and a new game can't be started in the middle of one.
+    gui.send("setoption name Threads value 2");
+    gui.send("setoption name Hash value 2");
+    gui.send("ucinewgame");
+    gui.wait_for("unable to start a new game while searching");
+    gui.send("stop");
+    gui.wait_for("bestmove");
+    gui.send("isready");
+    gui.wait_for("readyok");
+
+    gui.send("ucinewgame");
+    gui.send("go depth 5");
+    gui.wait_for("bestmove");
+    gui.send("quit");
+    gui.quit();
+}
@@ -0,0 +1,172 @@
+extern crate pleco;
+extern crate pleco_engine;
+
+mod common;
+
+use common::*;
+
+use pleco::Board;
+use pleco_engine::engine::{ID_NAME, VERSION};
+
+fn features() -> String {
+    format!(
+        "feature ping=1 setboard=1 usermove=1 time=1 draw=0 sigint=0 sigterm=0 reuse=1 \
+         analyze=1 colors=0 san=0 myname=\"{} {}\" variants=\"normal\" done=1\n",
+        ID_NAME, VERSION
+    )
+}
+
+/// Returns if `mov` is a move in coordinate notation, such as "e2e4" or "e7e8q".
+fn is_move(mov: &str) -> bool {
+    let b = mov.as_bytes();
+    (b.len() == 4 || (b.len() == 5 && b"nbrq".contains(&b[4])))
+        && (b'a'..=b'h').contains(&b[0])
+        && (b'1'..=b'8').contains(&b[1])
+        && (b'a'..=b'h').contains(&b[2])
+        && (b'1'..=b'8').contains(&b[3])
+}
+
+/// Splits a line of thinking output, "[ply] [score] [time] [nodes] [pv]", into its four
+/// numbers and the moves of the PV. Returns `None` for any other line.
+fn thinking(line: &str) -> Option<([i64; 4], Vec<&str>)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 5 || !fields[4..].iter().all(|mov| is_move(mov)) {
+        return None;
+    }
+    let mut numbers = [0; 4];
+    for (number, field) in numbers.iter_mut().zip(fields.iter()) {
+        *number = field.parse().ok()?;
+    }
+    Some((numbers, fields[4..].to_vec()))
+}
+
+/// Returns the move of a "move" line, after checking that it's legal on the board.
+fn played(board: &Board, line: &str) -> String {
+    let mov = line.trim_start_matches("move ").to_string();
+    assert!(
+        board.generate_moves().iter().any(|m| m.stringify() == mov),
+        "illegal move: {}",
+        line
+    );
+    mov
+}
+
+#[test]
+fn xboard_handshake() {
+    let transcript = run("xboard\n\
+                          protover 2\n\
+                          ping 7\n\
+                          foo\n\
+                          usermove e2e5\n\
+                          undo\n\
+                          setboard 8/8 w\n\
+                          quit\n\
+                          ping 8\n");
+    let expected = features()
+        + "pong 7\n\
+           Error (unknown command): foo\n\
+           Illegal move: e2e5\n\
+           Error (no moves to take back): undo\n\
+           tellusererror Illegal position\n";
+    assert_eq!(transcript, expected);
+}
+
+#[test]
+fn xboard_game() {
+    let gui = Gui::start();
+    let mut board = Board::start_pos();
+    gui.send("xboard");
+    gui.send("protover 2");
+    gui.wait_for("feature");
+    gui.send("new");
+    gui.send("sd 4");
+    gui.send("post");
+    gui.send("usermove e2e4");
+    board.apply_uci_move("e2e4");
+
+    let mov = played(&board, &gui.wait_for("move "));
+    board.apply_uci_move(&mov);
+
+    // The engine keeps track of its own move, so it can reply to the next one.
+    let reply = board.generate_moves()[0].stringify();
+    gui.send(&format!("usermove {}", reply));
+    board.apply_uci_move(&reply);
+    let mov = played(&board, &gui.wait_for("move "));
+    board.apply_uci_move(&mov);
+
+    // Moves are accepted on their own as well, following the last move of the engine.
+    let reply = board.generate_moves()[0].stringify();
+    gui.send(&reply);
+    board.apply_uci_move(&reply);
+    played(&board, &gui.wait_for("move "));
+    gui.send("quit");
+
+    let transcript = gui.quit();
+    assert!(!transcript.contains("Error"), "{}", transcript);
+    let lines = transcript.lines().filter_map(thinking).count();
+    assert!(lines > 0, "{}", transcript);
+}
+
+#[test]
+fn xboard_new_game() {
+    let gui = Gui::start();
+    gui.send("xboard");
+    gui.send("level 0 0:01 0");
+    gui.send("st 100");
+    gui.send("time 1");
+    gui.send("new");
+
+    // The time control is back to the default, so the engine moves well within 100 seconds.
+    gui.send("sd 3");
+    gui.send("go");
+    let mov = gui.wait_for("move ");
+    played(&Board::start_pos(), &mov);
+    gui.send("quit");
+    gui.quit();
+}
+
+#[test]
+fn xboard_force_and_undo() {
+    let gui = Gui::start();
+    gui.send("xboard");
+    gui.send("force");
+    gui.send("setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    gui.send("sd 4");
+    gui.send("go");
+    assert_eq!(gui.wait_for("move "), "move a1a8");
+
+    // With the move taken back, the engine finds it again instead of being mated.
+    gui.send("force");
+    gui.send("undo");
+    gui.send("go");
+    assert_eq!(gui.wait_for("move "), "move a1a8");
+
+    gui.send("force");
+    gui.send("setboard 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1");
+    gui.send("go");
+    gui.wait_for("1/2-1/2 {Stalemate}");
+    gui.send("quit");
+    gui.quit();
+}
+
+#[test]
+fn xboard_analyze() {
+    let gui = Gui::start();
+    gui.send("xboard");
+    gui.send("post");
+    gui.send("setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    gui.send("analyze");
+    let line = gui.wait_until("of thinking output at ply 3", |line| {
+        thinking(line).map_or(false, |(numbers, _)| numbers[0] == 3)
+    });
+    let (numbers, pv) = thinking(&line).unwrap();
+    assert_eq!(numbers[1], 100_001, "{}", line);
+    assert!(numbers[2] >= 0 && numbers[3] > 0, "{}", line);
+    assert_eq!(pv, vec!["a1a8"]);
+    gui.send("exit");
+    gui.send("ping 1");
+    gui.wait_for("pong 1");
+
+    let transcript = gui.quit();
+    assert!(!transcript.contains("move "), "{}", transcript);
+}